@@ -0,0 +1,131 @@
+//! Deadpool simple async pool for ClickHouse connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`clickhouse-rs`](https://crates.io/crates/clickhouse-rs)
+//! which uses the native protocol of ClickHouse.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use clickhouse_rs::{Block, Options};
+//! use deadpool_clickhouse::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let options: Options = "tcp://127.0.0.1:9000/default?compression=lz4"
+//!         .parse()
+//!         .unwrap();
+//!     let pool = Pool::new(Manager::new(options), 4);
+//!     let mut client = pool.get().await.unwrap();
+//!     client
+//!         .execute("CREATE TABLE IF NOT EXISTS numbers (n UInt32) ENGINE = Memory")
+//!         .await
+//!         .unwrap();
+//!     let block = Block::new().column("n", vec![1u32, 2, 3]);
+//!     client.insert_block("numbers", block).await.unwrap();
+//!     let block = client.query_all("SELECT n FROM numbers").await.unwrap();
+//!     for row in block.rows() {
+//!         let n: u32 = row.get("n").unwrap();
+//!         println!("{}", n);
+//!     }
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::ops::{Deref, DerefMut};
+
+use async_trait::async_trait;
+use clickhouse_rs::errors::Error;
+use clickhouse_rs::types::{Block, Complex};
+use clickhouse_rs::{ClientHandle, Options};
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with `clickhouse-rs`
+pub type Pool = deadpool::Pool<Client, Error>;
+
+/// A type alias for using `deadpool::Object` with `clickhouse-rs`
+pub type Object = deadpool::Object<Client, Error>;
+
+/// The manager for creating and recyling ClickHouse connections
+pub struct Manager {
+    options: Options,
+}
+
+impl Manager {
+    /// Create manager using the given `clickhouse_rs::Options`. Settings
+    /// like compression and timeouts are used for all connections.
+    pub fn new(options: Options) -> Self {
+        // Every handle is taken from a `clickhouse_rs::Pool` which holds
+        // exactly one connection. Pooling is done by deadpool.
+        Manager {
+            options: options.pool_min(0).pool_max(1),
+        }
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Client, Error> for Manager {
+    async fn create(&self) -> Result<Client, Error> {
+        let handle = clickhouse_rs::Pool::new(self.options.clone())
+            .get_handle()
+            .await?;
+        Ok(Client { handle })
+    }
+    async fn recycle(&self, client: &mut Client) -> Result<(), Error> {
+        client.handle.ping().await
+    }
+}
+
+/// A wrapper for `clickhouse_rs::ClientHandle` with helpers for executing
+/// statements and inserting blocks
+pub struct Client {
+    handle: ClientHandle,
+}
+
+impl Client {
+    /// Execute a statement which does not return any rows, e.g. a `CREATE`
+    /// or `ALTER` statement
+    pub async fn execute(&mut self, sql: &str) -> Result<(), Error> {
+        self.handle.execute(sql).await
+    }
+    /// Run the query and return all rows as a single block
+    pub async fn query_all(&mut self, sql: &str) -> Result<Block<Complex>, Error> {
+        self.handle.query(sql).fetch_all().await
+    }
+    /// Insert the rows of the given block into the given table
+    pub async fn insert_block(&mut self, table: &str, block: Block) -> Result<(), Error> {
+        self.handle.insert(table, block).await
+    }
+    /// Insert the rows of the given blocks into the given table. Every
+    /// block is sent as a separate `INSERT` so large amounts of data can be
+    /// inserted in batches of a reasonable size.
+    pub async fn insert_blocks<I>(&mut self, table: &str, blocks: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = Block>,
+    {
+        for block in blocks {
+            self.handle.insert(table, block).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Deref for Client {
+    type Target = ClientHandle;
+    fn deref(&self) -> &ClientHandle {
+        &self.handle
+    }
+}
+
+impl DerefMut for Client {
+    fn deref_mut(&mut self) -> &mut ClientHandle {
+        &mut self.handle
+    }
+}
@@ -0,0 +1,50 @@
+use std::env;
+
+use clickhouse_rs::{Block, Options};
+use deadpool_clickhouse::{Manager, Pool};
+
+fn create_pool() -> Pool {
+    let url = env::var("CLICKHOUSE_URL")
+        .unwrap_or_else(|_| "tcp://127.0.0.1:9000/default?compression=lz4".into());
+    let options: Options = url.parse().unwrap();
+    Pool::new(Manager::new(options), 4)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool();
+    let mut client = pool.get().await.unwrap();
+    let block = client.query_all("SELECT 1 + 2 AS n").await.unwrap();
+    let n: u16 = block.get(0, "n").unwrap();
+    assert_eq!(n, 3);
+}
+
+#[tokio::main]
+#[test]
+async fn test_insert_blocks() {
+    let pool = create_pool();
+    let mut client = pool.get().await.unwrap();
+    client
+        .execute("DROP TABLE IF EXISTS deadpool_insert_blocks")
+        .await
+        .unwrap();
+    client
+        .execute("CREATE TABLE deadpool_insert_blocks (n UInt32) ENGINE = Memory")
+        .await
+        .unwrap();
+    let blocks = vec![
+        Block::new().column("n", vec![1u32, 2]),
+        Block::new().column("n", vec![3u32]),
+    ];
+    client
+        .insert_blocks("deadpool_insert_blocks", blocks)
+        .await
+        .unwrap();
+    let block = client
+        .query_all("SELECT count() AS count FROM deadpool_insert_blocks")
+        .await
+        .unwrap();
+    let count: u64 = block.get(0, "count").unwrap();
+    assert_eq!(count, 3);
+}
@@ -0,0 +1,72 @@
+use deadpool::PoolConfig;
+use hyper::Uri;
+use serde::Deserialize;
+
+use crate::{Error, Manager, Pool};
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+///
+/// # Example (reading from the environment)
+///
+/// ```rust
+/// use deadpool_http::Config;
+///
+/// std::env::set_var("UPSTREAM__URL", "http://127.0.0.1:8080");
+/// std::env::set_var("UPSTREAM__POOL__MAX_SIZE", "8");
+/// let cfg = Config::from_env("UPSTREAM").unwrap();
+/// assert_eq!(cfg.url, Some("http://127.0.0.1:8080".to_string()));
+/// assert_eq!(cfg.pool.unwrap().max_size, 8);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// URL of the upstream server (e.g. `http://upstream:8080`). See
+    /// `Manager::from_uri`.
+    pub url: Option<String>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create new config object from an upstream URL
+    pub fn from_url(url: &str) -> Self {
+        Config {
+            url: Some(url.to_string()),
+            ..Self::default()
+        }
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `UPSTREAM__URL` and `UPSTREAM__POOL__MAX_SIZE` for the
+    /// prefix `UPSTREAM`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create manager using this configuration object
+    pub fn create_manager(&self) -> Result<Manager, Error> {
+        let url = self
+            .url
+            .as_ref()
+            .ok_or_else(|| Error::InvalidUri("url is not set".to_owned()))?;
+        let uri: Uri = url
+            .parse()
+            .map_err(|e: hyper::http::uri::InvalidUri| Error::InvalidUri(e.to_string()))?;
+        Manager::from_uri(&uri)
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Result<Pool, Error> {
+        Ok(Pool::from_config(
+            self.create_manager()?,
+            self.pool.clone().unwrap_or_default(),
+        ))
+    }
+}
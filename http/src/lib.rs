@@ -0,0 +1,238 @@
+//! Deadpool simple async pool for HTTP/1.1 connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for keep-alive HTTP/1.1 connections to a single upstream server
+//! using the low level connection API of
+//! [`hyper`](https://crates.io/crates/hyper). Unlike `hyper::Client` the
+//! size of the pool strictly limits the number of connections which are
+//! open to the upstream server, which is useful for proxies and gateways.
+//!
+//! Every connection handles one request at a time. The response must be
+//! read completely before the connection can be used for the next request.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_http::{Manager, Pool};
+//! use hyper::{Body, Request};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::new("127.0.0.1", 8080);
+//!     let pool = Pool::new(mgr, 16);
+//!     let mut conn = pool.get().await.unwrap();
+//!     let request = Request::get("/")
+//!         .header("Host", "127.0.0.1:8080")
+//!         .body(Body::empty())
+//!         .unwrap();
+//!     let response = conn.send_request(request).await.unwrap();
+//!     hyper::body::to_bytes(response.into_body()).await.unwrap();
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deadpool::timer::{self, TokioTimer};
+use futures::future::poll_fn;
+use hyper::client::conn::{self, SendRequest};
+use hyper::{Body, Request, Response, Uri};
+use log::warn;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with HTTP connections
+pub type Pool = deadpool::Pool<Connection, Error>;
+
+/// A type alias for using `deadpool::Object` with HTTP connections
+pub type Object = deadpool::Object<Connection, Error>;
+
+/// This error is returned by the `Manager` and `Connection`
+#[derive(Debug)]
+pub enum Error {
+    /// The upstream URI is invalid or uses an unsupported scheme
+    InvalidUri(String),
+    /// Connecting to the upstream server failed
+    Io(io::Error),
+    /// The HTTP connection returned an error
+    Http(hyper::Error),
+    /// The TLS handshake failed
+    #[cfg(feature = "tls")]
+    Tls(native_tls::Error),
+    /// The connection was closed by the upstream server
+    Closed,
+    /// The connection can not send another request right away, e.g.
+    /// because the body of the previous response was not read to the end
+    NotReady,
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<native_tls::Error> for Error {
+    fn from(e: native_tls::Error) -> Self {
+        Error::Tls(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidUri(msg) => write!(f, "Invalid upstream URI: {}", msg),
+            Error::Io(e) => write!(f, "Connecting to upstream failed: {}", e),
+            Error::Http(e) => write!(f, "{}", e),
+            #[cfg(feature = "tls")]
+            Error::Tls(e) => write!(f, "TLS handshake failed: {}", e),
+            Error::Closed => write!(f, "Connection was closed by upstream"),
+            Error::NotReady => write!(f, "Connection is not ready for another request"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Default of `Manager::with_ready_timeout`
+const DEFAULT_READY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The manager for creating and recyling HTTP/1.1 connections to a fixed
+/// upstream server
+pub struct Manager {
+    host: String,
+    port: u16,
+    ready_timeout: Duration,
+    #[cfg(feature = "tls")]
+    tls: Option<native_tls::TlsConnector>,
+}
+
+impl Manager {
+    /// Create manager for plain text connections to the given upstream
+    /// server
+    pub fn new(host: &str, port: u16) -> Self {
+        Manager {
+            host: host.to_owned(),
+            port,
+            ready_timeout: DEFAULT_READY_TIMEOUT,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+    /// Create manager from the given URI (e.g. `http://upstream:8080`).
+    /// Only the scheme, host and port of the URI are used. The scheme
+    /// `https` requires the `tls` feature.
+    pub fn from_uri(uri: &Uri) -> Result<Self, Error> {
+        let host = uri
+            .host()
+            .ok_or_else(|| Error::InvalidUri("missing host".to_owned()))?;
+        match uri.scheme_str() {
+            Some("http") | None => Ok(Self::new(host, uri.port_u16().unwrap_or(80))),
+            #[cfg(feature = "tls")]
+            Some("https") => Ok(Self::new(host, uri.port_u16().unwrap_or(443))
+                .with_tls(native_tls::TlsConnector::new()?)),
+            Some(scheme) => Err(Error::InvalidUri(format!("unsupported scheme: {}", scheme))),
+        }
+    }
+    /// Set how long recycling waits for a connection to become ready for
+    /// the next request. Connections which are still busy after this
+    /// time, e.g. because the body of the previous response was not read
+    /// to the end, are discarded. The default is 100 milliseconds.
+    pub fn with_ready_timeout(mut self, timeout: Duration) -> Self {
+        self.ready_timeout = timeout;
+        self
+    }
+    /// Use TLS for all connections. The host passed to `Manager::new` is
+    /// used for server name indication and certificate validation.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, connector: native_tls::TlsConnector) -> Self {
+        self.tls = Some(connector);
+        self
+    }
+    async fn handshake<T>(&self, io: T) -> Result<Connection, Error>
+    where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let (sender, connection) = conn::handshake(io).await?;
+        let closed = Arc::new(AtomicBool::new(false));
+        let closed_clone = closed.clone();
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                warn!(target: "deadpool.http", "Connection error: {}", e);
+            }
+            closed_clone.store(true, Ordering::Relaxed);
+        });
+        Ok(Connection { sender, closed })
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Connection, Error> for Manager {
+    async fn create(&self) -> Result<Connection, Error> {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        tcp.set_nodelay(true)?;
+        #[cfg(feature = "tls")]
+        {
+            if let Some(connector) = &self.tls {
+                let connector = tokio_tls::TlsConnector::from(connector.clone());
+                let tls = connector.connect(&self.host, tcp).await?;
+                return self.handshake(tls).await;
+            }
+        }
+        self.handshake(tcp).await
+    }
+    async fn recycle(&self, conn: &mut Connection) -> Result<(), Error> {
+        // An idle connection which was closed by the upstream server is
+        // detected by the background task or by `poll_ready`.
+        if conn.is_closed() {
+            return Err(Error::Closed);
+        }
+        // The background task may not have finished the previous response
+        // yet, so wait a little instead of discarding a healthy connection.
+        let ready = poll_fn(|cx| conn.sender.poll_ready(cx));
+        match timer::timeout(&TokioTimer, self.ready_timeout, ready).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(Error::NotReady),
+        }
+    }
+}
+
+/// A HTTP/1.1 connection to the upstream server
+pub struct Connection {
+    sender: SendRequest<Body>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Connection {
+    /// Send a request to the upstream server. The URI of the request
+    /// should only contain the path and query and a `Host` header should
+    /// be set.
+    pub async fn send_request(&mut self, request: Request<Body>) -> Result<Response<Body>, Error> {
+        poll_fn(|cx| self.sender.poll_ready(cx)).await?;
+        Ok(self.sender.send_request(request).await?)
+    }
+    /// Returns `true` if the connection was closed
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
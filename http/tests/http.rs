@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use deadpool_http::{Manager, Pool};
+use hyper::{Body, Request};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK";
+
+/// Start an upstream server which answers every request with `OK` and
+/// return its port and the number of accepted connections
+async fn start_upstream() -> (u16, Arc<AtomicUsize>) {
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            accepted.fetch_add(1, Ordering::Relaxed);
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                while let Ok(n) = socket.read(&mut buf).await {
+                    if n == 0 || socket.write_all(RESPONSE).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    (port, connections)
+}
+
+fn request() -> Request<Body> {
+    Request::get("/")
+        .header("Host", "127.0.0.1")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let (port, _) = start_upstream().await;
+    let pool = Pool::new(Manager::new("127.0.0.1", port), 2);
+    for _ in 0..4 {
+        let mut conn = pool.get().await.unwrap();
+        let response = conn.send_request(request()).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"OK");
+    }
+}
+
+#[tokio::main(threaded_scheduler)]
+#[test]
+async fn test_keep_alive() {
+    let (port, connections) = start_upstream().await;
+    let pool = Pool::new(Manager::new("127.0.0.1", port), 1);
+    for _ in 0..20 {
+        let mut conn = pool.get().await.unwrap();
+        let response = conn.send_request(request()).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"OK");
+    }
+    assert_eq!(connections.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::main]
+#[test]
+async fn test_invalid_scheme() {
+    let uri = "ftp://127.0.0.1".parse().unwrap();
+    assert!(Manager::from_uri(&uri).is_err());
+}
+
+#[tokio::main]
+#[test]
+async fn test_recycle_busy_connection() {
+    use std::time::Duration;
+    // The upstream server sends only part of the body and keeps the
+    // connection open, so the connection stays busy.
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            accepted.fetch_add(1, Ordering::Relaxed);
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                socket.read(&mut buf).await.unwrap();
+                socket
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nOK")
+                    .await
+                    .unwrap();
+                tokio::time::delay_for(Duration::from_secs(60)).await;
+            });
+        }
+    });
+    let pool = Pool::new(Manager::new("127.0.0.1", port), 1);
+    let mut conn = pool.get().await.unwrap();
+    let response = conn.send_request(request()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    drop(response);
+    drop(conn);
+    // The busy connection is discarded instead of waiting for it
+    let mut conn = tokio::time::timeout(Duration::from_secs(5), pool.get())
+        .await
+        .unwrap()
+        .unwrap();
+    let response = conn.send_request(request()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(connections.load(Ordering::Relaxed), 2);
+}
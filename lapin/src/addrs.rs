@@ -0,0 +1,217 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Addresses which failed to connect are skipped for this duration
+const EXCLUSION_PERIOD: Duration = Duration::from_secs(30);
+
+/// This enum is used to control how new connections are distributed
+/// across multiple broker addresses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddrSelection {
+    /// Try the addresses in the order they were specified. This is the
+    /// default.
+    InOrder,
+    /// Start with the next address for every new connection so the
+    /// connections are spread across all brokers.
+    RoundRobin,
+}
+
+impl Default for AddrSelection {
+    fn default() -> Self {
+        AddrSelection::InOrder
+    }
+}
+
+struct AddrState {
+    addr: String,
+    /// Time of the last failed connection attempt
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl AddrState {
+    fn is_excluded(&self) -> bool {
+        match *self.failed_at.lock().unwrap() {
+            Some(failed_at) => failed_at.elapsed() < EXCLUSION_PERIOD,
+            None => false,
+        }
+    }
+}
+
+/// Keeps track of the broker addresses which recently failed.
+pub(crate) struct Addrs {
+    addrs: Vec<AddrState>,
+    selection: AddrSelection,
+    next: AtomicUsize,
+}
+
+impl Addrs {
+    pub(crate) fn new(addrs: Vec<String>) -> Self {
+        assert!(!addrs.is_empty(), "at least one address is required");
+        Addrs {
+            addrs: addrs
+                .into_iter()
+                .map(|addr| AddrState {
+                    addr,
+                    failed_at: Mutex::new(None),
+                })
+                .collect(),
+            selection: AddrSelection::default(),
+            next: AtomicUsize::new(0),
+        }
+    }
+    /// Add the `heartbeat` query parameter to all addresses
+    pub(crate) fn set_heartbeat(&mut self, heartbeat: Duration) {
+        for state in &mut self.addrs {
+            state.addr = with_heartbeat(&state.addr, heartbeat);
+        }
+    }
+    pub(crate) fn set_selection(&mut self, selection: AddrSelection) {
+        self.selection = selection;
+    }
+    /// The order in which the addresses should be tried for the next
+    /// connection. Addresses which recently failed are moved to the end so
+    /// they are only used if all other addresses fail, too.
+    pub(crate) fn order(&self) -> Vec<usize> {
+        let len = self.addrs.len();
+        let start = match self.selection {
+            AddrSelection::InOrder => 0,
+            AddrSelection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed),
+        };
+        let (mut order, excluded): (Vec<usize>, Vec<usize>) = (0..len)
+            .map(|i| (start + i) % len)
+            .partition(|&index| !self.addrs[index].is_excluded());
+        order.extend(excluded);
+        order
+    }
+    pub(crate) fn addr(&self, index: usize) -> &str {
+        &self.addrs[index].addr
+    }
+    pub(crate) fn mark_failed(&self, index: usize) {
+        *self.addrs[index].failed_at.lock().unwrap() = Some(Instant::now());
+    }
+    pub(crate) fn mark_healthy(&self, index: usize) {
+        *self.addrs[index].failed_at.lock().unwrap() = None;
+    }
+}
+
+/// Add or replace the `heartbeat` query parameter of an AMQP URI. The
+/// heartbeat is negotiated in whole seconds so fractions of a second are
+/// rounded up.
+fn with_heartbeat(addr: &str, heartbeat: Duration) -> String {
+    let (base, query) = match addr.find('?') {
+        Some(index) => (&addr[..index], &addr[index + 1..]),
+        None => (addr, ""),
+    };
+    let mut secs = heartbeat.as_secs();
+    if heartbeat.subsec_nanos() > 0 {
+        secs += 1;
+    }
+    let heartbeat = format!("heartbeat={}", secs);
+    let mut replaced = false;
+    let mut params: Vec<String> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            if param.starts_with("heartbeat=") {
+                replaced = true;
+                heartbeat.clone()
+            } else {
+                param.to_owned()
+            }
+        })
+        .collect();
+    if !replaced {
+        params.push(heartbeat);
+    }
+    format!("{}?{}", base, params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs() -> Addrs {
+        Addrs::new(vec![
+            "amqp://node1:5672/%2f".to_string(),
+            "amqp://node2:5672/%2f".to_string(),
+            "amqp://node3:5672/%2f".to_string(),
+        ])
+    }
+
+    #[test]
+    fn test_order_in_order() {
+        let addrs = addrs();
+        assert_eq!(addrs.order(), vec![0, 1, 2]);
+        assert_eq!(addrs.order(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_order_round_robin() {
+        let mut addrs = addrs();
+        addrs.set_selection(AddrSelection::RoundRobin);
+        assert_eq!(addrs.order(), vec![0, 1, 2]);
+        assert_eq!(addrs.order(), vec![1, 2, 0]);
+        assert_eq!(addrs.order(), vec![2, 0, 1]);
+        assert_eq!(addrs.order(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_order_failed() {
+        let addrs = addrs();
+        addrs.mark_failed(0);
+        assert_eq!(addrs.order(), vec![1, 2, 0]);
+        addrs.mark_failed(2);
+        assert_eq!(addrs.order(), vec![1, 0, 2]);
+        addrs.mark_healthy(0);
+        assert_eq!(addrs.order(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_with_heartbeat() {
+        let heartbeat = Duration::from_secs(10);
+        assert_eq!(
+            with_heartbeat("amqp://127.0.0.1:5672/%2f", heartbeat),
+            "amqp://127.0.0.1:5672/%2f?heartbeat=10"
+        );
+        assert_eq!(
+            with_heartbeat("amqp://127.0.0.1:5672/%2f?frame_max=8192", heartbeat),
+            "amqp://127.0.0.1:5672/%2f?frame_max=8192&heartbeat=10"
+        );
+        assert_eq!(
+            with_heartbeat("amqp://127.0.0.1:5672/%2f?heartbeat=60", heartbeat),
+            "amqp://127.0.0.1:5672/%2f?heartbeat=10"
+        );
+        assert_eq!(
+            with_heartbeat(
+                "amqp://127.0.0.1:5672/%2f?heartbeat=60&frame_max=8192",
+                heartbeat
+            ),
+            "amqp://127.0.0.1:5672/%2f?heartbeat=10&frame_max=8192"
+        );
+    }
+
+    #[test]
+    fn test_with_heartbeat_rounding() {
+        let addr = "amqp://127.0.0.1:5672/%2f";
+        assert_eq!(
+            with_heartbeat(addr, Duration::from_millis(500)),
+            "amqp://127.0.0.1:5672/%2f?heartbeat=1"
+        );
+        assert_eq!(
+            with_heartbeat(addr, Duration::from_millis(1500)),
+            "amqp://127.0.0.1:5672/%2f?heartbeat=2"
+        );
+        assert_eq!(
+            with_heartbeat(addr, Duration::from_secs(0)),
+            "amqp://127.0.0.1:5672/%2f?heartbeat=0"
+        );
+    }
+
+    #[test]
+    fn test_set_heartbeat() {
+        let mut addrs = addrs();
+        addrs.set_heartbeat(Duration::from_secs(5));
+        assert_eq!(addrs.addr(1), "amqp://node2:5672/%2f?heartbeat=5");
+    }
+}
@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deadpool::timer::Timer;
+use futures::future::BoxFuture;
+use lapin::{options::BasicQosOptions, ConnectionProperties, Error};
+
+use crate::{Manager, Metrics, Topology};
+
+/// A type alias for using `deadpool::Pool` with `lapin::Channel`
+pub type ChannelPool = deadpool::Pool<lapin::Channel, Error>;
+
+/// A type alias for using `deadpool::Object` with `lapin::Channel`
+pub type Channel = deadpool::Object<lapin::Channel, Error>;
+
+/// Interval for checking whether another task finished connecting when
+/// all shared connections are currently being (re)connected
+const CONNECTING_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The manager for creating and recycling lapin channels.
+///
+/// AMQP multiplexes channels over a single connection. Rather than pooling
+/// whole connections this manager opens up to `max_connections`
+/// connections which are shared by all channels of the pool. New channels
+/// are distributed across those connections in a round-robin fashion.
+/// Connections which are no longer usable are replaced when the next
+/// channel is created on them.
+///
+/// The shared connections are created by a `Manager`, so retries, multiple
+/// addresses, TLS, the error callback and the handling of blocked
+/// connections work the same way as for a `Pool`. See
+/// `ChannelManager::from_manager`.
+pub struct ChannelManager {
+    manager: Manager,
+    slots: Mutex<Vec<Slot>>,
+    next: AtomicUsize,
+    prefetch_count: Option<u16>,
+}
+
+enum Slot {
+    Empty,
+    Connecting,
+    Ready(Arc<lapin::Connection>),
+}
+
+/// Resets a slot which is still connecting when dropped, i.e. if creating
+/// the connection failed or the future creating it was dropped
+struct ConnectingSlot<'a> {
+    slots: &'a Mutex<Vec<Slot>>,
+    index: usize,
+}
+
+impl Drop for ConnectingSlot<'_> {
+    fn drop(&mut self) {
+        let mut slots = self.slots.lock().unwrap();
+        if let Slot::Connecting = slots[self.index] {
+            slots[self.index] = Slot::Empty;
+        }
+    }
+}
+
+/// The result of picking a connection for a new channel
+enum Pick {
+    Ready(Arc<lapin::Connection>),
+    Connect(usize),
+    Wait,
+}
+
+impl ChannelManager {
+    /// Create manager using the AMQP address and `ConnectionProperties`.
+    /// The channels are opened on up to `max_connections` shared
+    /// connections.
+    pub fn new(
+        addr: String,
+        connection_properties: ConnectionProperties,
+        max_connections: usize,
+    ) -> Self {
+        Self::from_manager(Manager::new(addr, connection_properties), max_connections)
+    }
+    /// Create manager which uses the given `Manager` for creating up to
+    /// `max_connections` shared connections
+    pub fn from_manager(manager: Manager, max_connections: usize) -> Self {
+        assert!(
+            max_connections > 0,
+            "max_connections must be greater than 0"
+        );
+        Self {
+            manager,
+            slots: Mutex::new((0..max_connections).map(|_| Slot::Empty).collect()),
+            next: AtomicUsize::new(0),
+            prefetch_count: None,
+        }
+    }
+    /// Create manager using the AMQP address and default
+    /// `ConnectionProperties` which use the executor and reactor of the
+    /// current `tokio` runtime. See `Manager::new_tokio`.
+    #[cfg(feature = "rt-tokio")]
+    pub fn new_tokio(addr: String, max_connections: usize) -> Self {
+        Self::from_manager(Manager::new_tokio(addr), max_connections)
+    }
+    /// Set the `connection_name` client property of the shared connections.
+    /// See `Manager::with_connection_name`.
+    pub fn with_connection_name(mut self, name: &str) -> Self {
+        self.manager = self.manager.with_connection_name(name);
+        self
+    }
+    /// Declare the given exchanges, queues and bindings on every new
+    /// connection before channels are created on it, e.g. after a broker
+    /// restart.
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.manager = self.manager.with_topology(topology);
+        self
+    }
+    /// Set an async hook which is called with every new connection. See
+    /// `Manager::with_post_create`.
+    pub fn with_post_create<F>(mut self, hook: F) -> Self
+    where
+        F: for<'c> Fn(&'c lapin::Connection) -> BoxFuture<'c, Result<(), Error>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.manager = self.manager.with_post_create(hook);
+        self
+    }
+    /// Set the prefetch count of every new channel via `basic_qos` so
+    /// consumers do not need to set it themselves.
+    pub fn with_prefetch_count(mut self, prefetch_count: u16) -> Self {
+        self.prefetch_count = Some(prefetch_count);
+        self
+    }
+    /// Retrieve a handle to the connection and channel counters of this
+    /// manager
+    pub fn metrics(&self) -> Metrics {
+        self.manager.metrics()
+    }
+    /// Number of connections currently used by the channels of the pool
+    pub fn connection_count(&self) -> usize {
+        self.slots
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|slot| matches!(slot, Slot::Ready(_)))
+            .count()
+    }
+    /// Pick a usable connection or a slot which needs a new connection.
+    /// Empty slots are filled first.
+    fn pick(&self, slots: &mut [Slot]) -> Pick {
+        if let Some(index) = slots.iter().position(|slot| matches!(slot, Slot::Empty)) {
+            slots[index] = Slot::Connecting;
+            return Pick::Connect(index);
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        for i in 0..slots.len() {
+            let index = (start + i) % slots.len();
+            if let Slot::Ready(connection) = &slots[index] {
+                if self.manager.check_status(connection).is_ok() {
+                    return Pick::Ready(connection.clone());
+                }
+                self.manager.metrics.connection_closed();
+                self.manager.metrics.reconnect();
+                slots[index] = Slot::Connecting;
+                return Pick::Connect(index);
+            }
+        }
+        Pick::Wait
+    }
+    async fn connection(&self) -> Result<Arc<lapin::Connection>, Error> {
+        loop {
+            // The lock must not be held while connecting as this would
+            // block channels from being created on the other connections.
+            let pick = self.pick(&mut self.slots.lock().unwrap());
+            match pick {
+                Pick::Ready(connection) => return Ok(connection),
+                Pick::Connect(index) => {
+                    let slot = ConnectingSlot {
+                        slots: &self.slots,
+                        index,
+                    };
+                    let connection = Arc::new(deadpool::Manager::create(&self.manager).await?);
+                    slot.slots.lock().unwrap()[index] = Slot::Ready(connection.clone());
+                    return Ok(connection);
+                }
+                Pick::Wait => self.manager.timer.sleep(CONNECTING_POLL_INTERVAL).await,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<lapin::Channel, Error> for ChannelManager {
+    async fn create(&self) -> Result<lapin::Channel, Error> {
+        let connection = self.connection().await?;
+        let channel = connection.create_channel().await?;
+        if let Some(prefetch_count) = self.prefetch_count {
+            channel
+                .basic_qos(prefetch_count, BasicQosOptions::default())
+                .await?;
+        }
+        self.manager.metrics.channel_opened();
+        Ok(channel)
+    }
+    async fn recycle(&self, channel: &mut lapin::Channel) -> Result<(), Error> {
+        let status = channel.status();
+        if !status.connected() {
+            self.manager.metrics.channel_closed();
+            return Err(Error::InvalidChannelState(status.state()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+// Uses of `lapin` APIs which are likely to change between major versions go
+// through this module so that version specific code can be gated via the
+// `lapin-*` features in one place once another major version is supported.
+
+use lapin::ConnectionStatus;
+
+/// Create the TLS configuration for `Connection::connect_with_config` from
+/// PEM encoded root certificates and a PKCS#12 archive with its password
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) fn tls_config(
+    cert_chain: Option<String>,
+    identity: Option<(Vec<u8>, String)>,
+) -> lapin::tcp::OwnedTLSConfig {
+    use lapin::tcp::{OwnedIdentity, OwnedTLSConfig};
+    OwnedTLSConfig {
+        identity: identity.map(|(der, password)| OwnedIdentity { der, password }),
+        cert_chain,
+    }
+}
+
+/// Returns `true` if the broker blocked the connection, e.g. because of a
+/// resource alarm
+pub(crate) fn is_blocked(status: &ConnectionStatus) -> bool {
+    status.blocked()
+}
@@ -9,8 +9,6 @@
 //! # Example
 //!
 //! ```rust
-//! use std::env;
-//!
 //! use deadpool_lapin::{Manager, Pool};
 //! use lapin::{
 //!     ConnectionProperties,
@@ -20,9 +18,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() {
-//!     let addr = std::env::var("AMQP_ADDR").unwrap_or_else(
-//!         |_| "amqp://127.0.0.1:5672/%2f".into());
-//!     let mgr = Manager::new(addr, ConnectionProperties::default());
+//!     let mgr = Manager::from_env(ConnectionProperties::default());
 //!     let pool = Pool::new(mgr, 16);
 //!     for i in 1..10 {
 //!         let mut connection = pool.get().await.unwrap();
@@ -37,10 +33,85 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Supported versions of `lapin`
+//!
+//! The version of `lapin` is selected via features. Currently only the
+//! `lapin-1` feature (default) exists which uses `lapin` 1.x. Features for
+//! new major versions will be added once they are released on crates.io.
+//! The selected version is re-exported as `deadpool_lapin::lapin` so
+//! applications can use the same version as this crate.
 #![warn(missing_docs)]
 
+#[cfg(not(feature = "lapin-1"))]
+compile_error!("The `lapin-1` feature must be enabled");
+
+#[cfg(feature = "lapin-1")]
+extern crate lapin_1 as lapin;
+
+pub use lapin;
+
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
-use lapin::{ConnectionProperties, Error};
+use deadpool::timer::{Timer, TokioTimer};
+use futures::future::BoxFuture;
+use lapin::{
+    tcp::OwnedTLSConfig,
+    types::{AMQPValue, LongString},
+    ConnectionProperties, ConnectionState, Error,
+};
+use log::warn;
+
+mod addrs;
+pub use addrs::AddrSelection;
+use addrs::Addrs;
+mod channel;
+mod compat;
+#[cfg(feature = "config")]
+mod config;
+pub use channel::{Channel, ChannelManager, ChannelPool};
+#[cfg(feature = "config")]
+pub use config::Config;
+mod metrics;
+pub use metrics::{Metrics, Stats};
+mod retry;
+mod shutdown;
+pub use retry::RetryConfig;
+pub use shutdown::ShutdownHandle;
+mod topology;
+pub use topology::{Binding, Exchange, Queue, Topology};
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+mod tls;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub use tls::{TlsConfig, TlsError};
+
+/// The AMQP reply code for closing a channel without an error
+const REPLY_SUCCESS: u16 = 200;
+
+/// The address which is used if no address is configured
+const DEFAULT_URL: &str = "amqp://127.0.0.1:5672/%2f";
+
+/// Set the `connection_name` client property which is shown by the
+/// management UI of RabbitMQ
+fn set_connection_name(connection_properties: &mut ConnectionProperties, name: &str) {
+    connection_properties.client_properties.insert(
+        "connection_name".into(),
+        AMQPValue::LongString(LongString::from(name)),
+    );
+}
+
+/// Callback which is called for every error of a connection created by the
+/// manager. See `Manager::with_error_callback`.
+pub type ErrorCallback = Arc<dyn Fn(&Error) + Send + Sync>;
+
+/// Async hook which is called with every new connection before it is
+/// handed out. See `Manager::with_post_create`.
+pub type PostCreateHook =
+    Arc<dyn for<'c> Fn(&'c lapin::Connection) -> BoxFuture<'c, Result<(), Error>> + Send + Sync>;
 
 /// A type alias for using `deadpool::Pool` with `lapin`
 pub type Pool = deadpool::Pool<lapin::Connection, Error>;
@@ -50,30 +121,311 @@ pub type Connection = deadpool::Object<lapin::Connection, Error>;
 
 /// The manager for creating and recyling lapin connections
 pub struct Manager {
-    addr: String,
+    addrs: Addrs,
     connection_properties: ConnectionProperties,
+    retry: RetryConfig,
+    tls: Option<OwnedTLSConfig>,
+    topology: Topology,
+    metrics: Metrics,
+    closing: Arc<AtomicBool>,
+    error_callback: Option<ErrorCallback>,
+    post_create: Option<PostCreateHook>,
+    blocked_grace_period: Duration,
+    /// Time when a blocked connection was first recycled. This is reset
+    /// once an unblocked connection is recycled.
+    blocked_since: Mutex<Option<Instant>>,
+    timer: Arc<dyn Timer>,
 }
 
 impl Manager {
-    /// Create manager using `PgConfig` and a `TlsConnector`
+    /// Create manager using the AMQP address and `ConnectionProperties`
     pub fn new(addr: String, connection_properties: ConnectionProperties) -> Self {
+        Self::with_addrs(vec![addr], connection_properties)
+    }
+    /// Create manager using the address of the `AMQP_URL` environment
+    /// variable. `AMQP_ADDR` is used if `AMQP_URL` is not set and
+    /// `amqp://127.0.0.1:5672/%2f` if neither is set.
+    pub fn from_env(connection_properties: ConnectionProperties) -> Self {
+        let addr = env::var("AMQP_URL")
+            .or_else(|_| env::var("AMQP_ADDR"))
+            .unwrap_or_else(|_| DEFAULT_URL.into());
+        Self::new(addr, connection_properties)
+    }
+    /// Create manager using multiple AMQP addresses, e.g. of the nodes of a
+    /// RabbitMQ cluster. New connections try the addresses in order until
+    /// one of them succeeds. Addresses which recently failed to connect
+    /// are only tried after all other addresses failed, too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `addrs` is empty.
+    pub fn with_addrs(addrs: Vec<String>, connection_properties: ConnectionProperties) -> Self {
         Self {
-            addr: addr,
+            addrs: Addrs::new(addrs),
             connection_properties: connection_properties,
+            retry: RetryConfig::default(),
+            tls: None,
+            topology: Topology::default(),
+            metrics: Metrics::default(),
+            closing: Arc::new(AtomicBool::new(false)),
+            error_callback: None,
+            post_create: None,
+            blocked_grace_period: Duration::from_secs(0),
+            blocked_since: Mutex::new(None),
+            timer: Arc::new(TokioTimer),
         }
     }
+    /// Configure how new connections are distributed across the addresses
+    pub fn with_addr_selection(mut self, selection: AddrSelection) -> Self {
+        self.addrs.set_selection(selection);
+        self
+    }
+    /// Create manager using the AMQP address and default
+    /// `ConnectionProperties` which use the executor and reactor of the
+    /// current `tokio` runtime instead of spawning threads of their own.
+    ///
+    /// This requires the `rt-tokio` feature and must be called from
+    /// within a `tokio` runtime.
+    #[cfg(feature = "rt-tokio")]
+    pub fn new_tokio(addr: String) -> Self {
+        use tokio_amqp::LapinTokioExt;
+        Self::new(addr, ConnectionProperties::default().with_tokio())
+    }
+    /// Configure how connecting to the broker is retried. See
+    /// `RetryConfig` for the defaults.
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+    /// Set the timer which is used for the retry backoff and by the
+    /// `ShutdownHandle`. Defaults to `deadpool::timer::TokioTimer`.
+    pub fn with_timer(mut self, timer: impl Timer + 'static) -> Self {
+        self.timer = Arc::new(timer);
+        self
+    }
+    /// Set the heartbeat interval which is negotiated with the broker by
+    /// adding the `heartbeat` query parameter to the addresses. If no
+    /// heartbeat is received within twice the interval the connection is
+    /// closed and is no longer handed out by the pool.
+    ///
+    /// The heartbeat is negotiated in whole seconds so fractions of a
+    /// second are rounded up. A heartbeat of zero disables heartbeats.
+    /// An existing `heartbeat` parameter of the addresses is replaced.
+    pub fn with_heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.addrs.set_heartbeat(heartbeat);
+        self
+    }
+    /// Keep handing out connections which are blocked by the broker (e.g.
+    /// because of a memory alarm) for the given grace period before
+    /// discarding them in favor of new connections. Resource alarms usually
+    /// affect all connections to a broker so the grace period starts when
+    /// the first blocked connection is recycled and ends once a connection
+    /// is no longer blocked. Defaults to zero which discards blocked
+    /// connections immediately.
+    pub fn with_blocked_grace_period(mut self, grace_period: Duration) -> Self {
+        self.blocked_grace_period = grace_period;
+        self
+    }
+    /// Set the `connection_name` client property of every connection so the
+    /// management UI of RabbitMQ shows which service or pool owns the
+    /// connection.
+    pub fn with_connection_name(mut self, name: &str) -> Self {
+        set_connection_name(&mut self.connection_properties, name);
+        self
+    }
+    /// Declare the given exchanges, queues and bindings on every new
+    /// connection before it is handed out, e.g. after a broker restart.
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+    /// Set a callback which is called whenever a connection created by this
+    /// manager reports an error, e.g. because the connection to the broker
+    /// was lost. The connection is discarded when it is recycled the next
+    /// time. Without a callback errors are logged.
+    pub fn with_error_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Error) + Send + Sync + 'static,
+    {
+        self.error_callback = Some(Arc::new(callback));
+        self
+    }
+    /// Set an async hook which is called with every new connection after
+    /// the topology was declared, e.g. for declaring dead-letter exchanges
+    /// or registering consumers. If the hook or declaring the topology
+    /// fails the connection is closed and the next address is tried, just
+    /// like when connecting fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mgr = Manager::from_env(ConnectionProperties::default())
+    ///     .with_post_create(|connection| {
+    ///         Box::pin(async move {
+    ///             let channel = connection.create_channel().await?;
+    ///             channel
+    ///                 .exchange_declare("dlx", ExchangeKind::Fanout, Default::default(), Default::default())
+    ///                 .await?;
+    ///             Ok(())
+    ///         })
+    ///     });
+    /// ```
+    pub fn with_post_create<F>(mut self, hook: F) -> Self
+    where
+        F: for<'c> Fn(&'c lapin::Connection) -> BoxFuture<'c, Result<(), Error>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.post_create = Some(Arc::new(hook));
+        self
+    }
+    /// Retrieve a handle to the connection counters of this manager
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+    /// Retrieve a handle for closing the connections of the pool gracefully
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            closing: self.closing.clone(),
+            metrics: self.metrics.clone(),
+            timer: self.timer.clone(),
+        }
+    }
+}
+
+impl Manager {
+    /// Try to connect to the addresses one after another
+    async fn connect(&self) -> Result<lapin::Connection, Error> {
+        let mut last_error = None;
+        for index in self.addrs.order() {
+            let addr = self.addrs.addr(index);
+            let properties = self.connection_properties.clone();
+            let result = match &self.tls {
+                Some(tls) => {
+                    lapin::Connection::connect_with_config(addr, properties, tls.clone()).await
+                }
+                None => lapin::Connection::connect(addr, properties).await,
+            };
+            match result {
+                Ok(connection) => {
+                    self.addrs.mark_healthy(index);
+                    match self.set_up(&connection).await {
+                        Ok(()) => return Ok(connection),
+                        Err(e) => {
+                            warn!(
+                                target: "deadpool.lapin",
+                                "Setting up connection to {} failed: {}", addr, e
+                            );
+                            let closed = connection.close(REPLY_SUCCESS, "Setup failed").await;
+                            if let Err(close_error) = closed {
+                                warn!(
+                                    target: "deadpool.lapin",
+                                    "Closing connection failed: {}", close_error
+                                );
+                            }
+                            last_error = Some(e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(target: "deadpool.lapin", "Connecting to {} failed: {}", addr, e);
+                    self.addrs.mark_failed(index);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap())
+    }
+    /// Declare the topology and run the post create hook
+    async fn set_up(&self, connection: &lapin::Connection) -> Result<(), Error> {
+        self.topology.declare_on(connection).await?;
+        if let Some(hook) = &self.post_create {
+            hook(connection).await?;
+        }
+        Ok(())
+    }
+    /// Count and forward the errors of the given connection
+    fn register_error_handler(&self, connection: &lapin::Connection) {
+        let metrics = self.metrics.clone();
+        let callback = self.error_callback.clone();
+        connection.on_error(move |error| {
+            metrics.connection_error();
+            match &callback {
+                Some(callback) => callback(&error),
+                None => warn!(target: "deadpool.lapin", "Connection error: {}", error),
+            }
+        });
+    }
+    /// Returns `true` if connections have been blocked for longer than the
+    /// `blocked_grace_period`
+    fn blocked_too_long(&self) -> bool {
+        let mut blocked_since = self.blocked_since.lock().unwrap();
+        blocked_since.get_or_insert_with(Instant::now).elapsed() >= self.blocked_grace_period
+    }
+    /// Check the state of the connection without a round trip to the
+    /// broker
+    fn check_status(&self, connection: &lapin::Connection) -> Result<(), Error> {
+        let status = connection.status();
+        // Connections which missed heartbeats are closed by `lapin`.
+        if !status.connected() {
+            return Err(Error::InvalidConnectionState(status.state()));
+        }
+        // Connections which are blocked by the broker because of a resource
+        // alarm would block publishers so they are dropped once the grace
+        // period is over.
+        if compat::is_blocked(&status) {
+            if self.blocked_too_long() {
+                return Err(Error::InvalidConnectionState(status.state()));
+            }
+        } else {
+            *self.blocked_since.lock().unwrap() = None;
+        }
+        Ok(())
+    }
+    async fn check_health(&self, connection: &lapin::Connection) -> Result<(), Error> {
+        self.check_status(connection)?;
+        // Opening and closing a channel requires a round trip to the broker
+        // and detects connections which are dead but were not noticed yet.
+        let channel = connection.create_channel().await?;
+        channel.close(REPLY_SUCCESS, "OK").await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl deadpool::Manager<lapin::Connection, Error> for Manager {
     async fn create(&self) -> Result<lapin::Connection, Error> {
-        let connection =
-            lapin::Connection::connect(self.addr.as_str(), self.connection_properties.clone())
-                .await?;
-        Ok(connection)
+        if self.closing.load(Ordering::Relaxed) {
+            return Err(Error::InvalidConnectionState(ConnectionState::Closing));
+        }
+        let mut retry = 0;
+        loop {
+            match self.connect().await {
+                Ok(connection) => {
+                    self.register_error_handler(&connection);
+                    self.metrics.connection_opened();
+                    return Ok(connection);
+                }
+                Err(e) if retry + 1 < self.retry.max_attempts as u32 => {
+                    retry += 1;
+                    self.metrics.reconnect();
+                    let backoff = self.retry.backoff(retry);
+                    warn!(
+                        target: "deadpool.lapin",
+                        "Connecting failed, retrying in {:?}: {}", backoff, e
+                    );
+                    self.timer.sleep(backoff).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
     async fn recycle(&self, connection: &mut lapin::Connection) -> Result<(), Error> {
-        // FIXME how to check the health?
-        Ok(())
+        let result = self.check_health(connection).await;
+        if result.is_err() {
+            self.metrics.connection_closed();
+        }
+        result
     }
 }
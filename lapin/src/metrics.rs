@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A snapshot of the connection and channel counters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of connections which are currently open
+    pub connections: isize,
+    /// Number of channels which are currently open
+    pub channels: isize,
+    /// Number of connection attempts which were retried or replaced a
+    /// connection which was lost
+    pub reconnects: u64,
+    /// Number of errors reported by connections, e.g. a lost connection to
+    /// the broker
+    pub connection_errors: u64,
+    /// Number of publish errors reported via `Metrics::record_publish_error`
+    pub publish_errors: u64,
+}
+
+#[derive(Default)]
+struct MetricsInner {
+    connections: AtomicIsize,
+    channels: AtomicIsize,
+    reconnects: AtomicU64,
+    connection_errors: AtomicU64,
+    publish_errors: AtomicU64,
+}
+
+/// Connection and channel counters of a manager.
+///
+/// This is a cheap handle which can be cloned and kept around after the
+/// manager was moved into the pool. See `Manager::metrics` and
+/// `ChannelManager::metrics`.
+///
+/// Connections and channels are counted as closed when they fail to be
+/// recycled. Objects which are dropped together with the pool are not
+/// accounted for.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    inner: Arc<MetricsInner>,
+}
+
+impl Metrics {
+    /// Retrieve a snapshot of the counters
+    pub fn stats(&self) -> Stats {
+        Stats {
+            connections: self.inner.connections.load(Ordering::Relaxed),
+            channels: self.inner.channels.load(Ordering::Relaxed),
+            reconnects: self.inner.reconnects.load(Ordering::Relaxed),
+            connection_errors: self.inner.connection_errors.load(Ordering::Relaxed),
+            publish_errors: self.inner.publish_errors.load(Ordering::Relaxed),
+        }
+    }
+    /// Count a failed publish. The pool can not observe publishes so this
+    /// needs to be called by the application in order to report publish
+    /// errors together with the other counters.
+    pub fn record_publish_error(&self) {
+        self.inner.publish_errors.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(crate) fn connection_opened(&self) {
+        self.inner.connections.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(crate) fn connection_closed(&self) {
+        self.inner.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+    pub(crate) fn channel_opened(&self) {
+        self.inner.channels.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(crate) fn channel_closed(&self) {
+        self.inner.channels.fetch_sub(1, Ordering::Relaxed);
+    }
+    pub(crate) fn connection_error(&self) {
+        self.inner.connection_errors.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(crate) fn reconnect(&self) {
+        self.inner.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Configuration of the retries of `Manager::create`.
+///
+/// When the broker is not reachable, e.g. while RabbitMQ is restarting,
+/// connecting is retried after a delay. The delay starts at
+/// `initial_backoff` and is doubled after every failed attempt up to
+/// `max_backoff`. A random jitter of up to 50% of the delay is subtracted
+/// so that the connections of multiple pools and processes do not all
+/// reconnect at the same time.
+#[derive(Clone, Debug)]
+pub struct RetryConfig {
+    /// Maximum number of connection attempts. `1` disables retries.
+    pub max_attempts: usize,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper limit of the delay between two attempts
+    pub max_backoff: Duration,
+    /// Randomize the delay between two attempts
+    pub jitter: bool,
+}
+
+impl RetryConfig {
+    /// Configuration which does not retry at all
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+    /// Delay before the given retry. The first retry is `1`.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff - backoff.mul_f64(rand::thread_rng().gen_range(0.0, 0.5))
+        } else {
+            backoff
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(jitter: bool) -> RetryConfig {
+        RetryConfig {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            jitter,
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let retry = config(false);
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(400));
+        assert_eq!(retry.backoff(4), Duration::from_millis(800));
+        assert_eq!(retry.backoff(5), Duration::from_secs(1));
+        assert_eq!(retry.backoff(100), Duration::from_secs(1));
+        assert_eq!(retry.backoff(u32::max_value()), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let retry = config(true);
+        for retry_number in 1..10 {
+            let max = config(false).backoff(retry_number);
+            let backoff = retry.backoff(retry_number);
+            assert!(backoff <= max);
+            assert!(backoff >= max / 2);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use deadpool::timer::{self, Timer};
+use deadpool::Object;
+use log::warn;
+
+use crate::{Metrics, Pool, REPLY_SUCCESS};
+
+/// Interval for checking whether connections which are in use were
+/// returned to the pool
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A handle for closing all connections of a pool gracefully.
+///
+/// Dropping a connection closes the socket without closing the AMQP
+/// connection first which makes the broker log an error. This handle closes
+/// the connections properly instead. See `Manager::shutdown_handle`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use std::time::Duration;
+///
+/// use deadpool_lapin::{Manager, Pool};
+/// use lapin::ConnectionProperties;
+///
+/// let mgr = Manager::from_env(ConnectionProperties::default());
+/// let shutdown = mgr.shutdown_handle();
+/// let pool = Pool::new(mgr, 16);
+/// // ...
+/// shutdown.close(&pool, Duration::from_secs(5)).await;
+/// ```
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    pub(crate) closing: Arc<AtomicBool>,
+    pub(crate) metrics: Metrics,
+    pub(crate) timer: Arc<dyn Timer>,
+}
+
+impl ShutdownHandle {
+    /// Returns `true` if `close` was called. The manager no longer creates
+    /// new connections once the pool is closing.
+    pub fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::Relaxed)
+    }
+    /// Close all connections of the pool. Connections which are currently
+    /// in use are closed as soon as they are returned to the pool, e.g.
+    /// after outstanding publishes were completed. Connections which are
+    /// not returned within the `grace` period are left alone.
+    pub async fn close(&self, pool: &Pool, grace: Duration) {
+        self.closing.store(true, Ordering::Relaxed);
+        let deadline = Instant::now() + grace;
+        while self.metrics.stats().connections > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match timer::timeout(&*self.timer, deadline - now, pool.get()).await {
+                Ok(Ok(connection)) => {
+                    let connection = Object::take(connection);
+                    self.metrics.connection_closed();
+                    if let Err(e) = connection.close(REPLY_SUCCESS, "Pool closed").await {
+                        warn!(target: "deadpool.lapin", "Closing connection failed: {}", e);
+                    }
+                }
+                // All remaining connections are in use
+                Ok(Err(_)) => self.timer.sleep(POLL_INTERVAL).await,
+                Err(_) => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use lapin::tcp::OwnedTLSConfig;
+use lapin::ConnectionProperties;
+
+use crate::{compat, Manager};
+
+/// Configuration of TLS connections to the broker using `amqps://`
+/// addresses.
+///
+/// The TLS implementation is selected by enabling either the `native-tls`
+/// or the `rustls` feature of this crate.
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+    /// PEM encoded root certificates which are trusted when connecting to
+    /// the broker
+    pub root_certs: Vec<PathBuf>,
+    /// DER encoded PKCS#12 archive containing the client certificate and
+    /// private key. This is only supported by `native-tls`, not by
+    /// `rustls`.
+    pub client_identity: Option<PathBuf>,
+    /// Password of the `client_identity` archive
+    pub client_identity_password: Option<String>,
+}
+
+/// This error is returned if the `TlsConfig` could not be loaded.
+#[derive(Debug)]
+pub enum TlsError {
+    /// A certificate or key file could not be read
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsError::Io(path, e) => write!(f, "Could not read {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, TlsError> {
+    fs::read(path).map_err(|e| TlsError::Io(path.to_owned(), e))
+}
+
+impl TlsConfig {
+    /// Load the certificates and create the `OwnedTLSConfig` used by
+    /// `lapin`
+    pub fn to_owned_tls_config(&self) -> Result<OwnedTLSConfig, TlsError> {
+        let mut cert_chain = None;
+        for path in &self.root_certs {
+            let pem = fs::read_to_string(path).map_err(|e| TlsError::Io(path.clone(), e))?;
+            cert_chain.get_or_insert_with(String::new).push_str(&pem);
+        }
+        let identity = match &self.client_identity {
+            Some(path) => Some((
+                read_file(path)?,
+                self.client_identity_password.clone().unwrap_or_default(),
+            )),
+            None => None,
+        };
+        Ok(compat::tls_config(cert_chain, identity))
+    }
+}
+
+impl Manager {
+    /// Create manager using `amqps://` addresses and the TLS implementation
+    /// selected via the features of this crate which is configured by the
+    /// given `TlsConfig`
+    pub fn new_tls(
+        addrs: Vec<String>,
+        connection_properties: ConnectionProperties,
+        tls_config: &TlsConfig,
+    ) -> Result<Self, TlsError> {
+        let mut manager = Self::with_addrs(addrs, connection_properties);
+        manager.tls = Some(tls_config.to_owned_tls_config()?);
+        Ok(manager)
+    }
+}
@@ -0,0 +1,144 @@
+use lapin::options::{ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions};
+use lapin::types::FieldTable;
+use lapin::{Channel, Connection, Error, ExchangeKind};
+
+use crate::REPLY_SUCCESS;
+
+/// An exchange which is declared by `Topology::declare`
+#[derive(Clone, Debug)]
+pub struct Exchange {
+    /// Name of the exchange
+    pub name: String,
+    /// Type of the exchange
+    pub kind: ExchangeKind,
+    /// Options passed to `exchange_declare`
+    pub options: ExchangeDeclareOptions,
+    /// Arguments passed to `exchange_declare`
+    pub arguments: FieldTable,
+}
+
+/// A queue which is declared by `Topology::declare`
+#[derive(Clone, Debug)]
+pub struct Queue {
+    /// Name of the queue
+    pub name: String,
+    /// Options passed to `queue_declare`
+    pub options: QueueDeclareOptions,
+    /// Arguments passed to `queue_declare`
+    pub arguments: FieldTable,
+}
+
+/// A binding of a queue to an exchange which is declared by
+/// `Topology::declare`
+#[derive(Clone, Debug)]
+pub struct Binding {
+    /// Name of the queue
+    pub queue: String,
+    /// Name of the exchange
+    pub exchange: String,
+    /// Routing key of the binding
+    pub routing_key: String,
+    /// Options passed to `queue_bind`
+    pub options: QueueBindOptions,
+    /// Arguments passed to `queue_bind`
+    pub arguments: FieldTable,
+}
+
+/// The exchanges, queues and bindings which are declared on every new
+/// connection.
+///
+/// Non-durable exchanges and queues are lost when the broker restarts.
+/// Registering them with the manager via `Manager::with_topology` or
+/// `ChannelManager::with_topology` makes sure they are declared again
+/// before a new connection or channel is handed out.
+#[derive(Clone, Debug, Default)]
+pub struct Topology {
+    /// Exchanges which are declared first
+    pub exchanges: Vec<Exchange>,
+    /// Queues which are declared after the exchanges
+    pub queues: Vec<Queue>,
+    /// Bindings which are declared after the queues
+    pub bindings: Vec<Binding>,
+}
+
+impl Topology {
+    /// Create an empty topology
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an exchange using the default options and no arguments
+    pub fn exchange(mut self, name: &str, kind: ExchangeKind) -> Self {
+        self.exchanges.push(Exchange {
+            name: name.to_owned(),
+            kind,
+            options: ExchangeDeclareOptions::default(),
+            arguments: FieldTable::default(),
+        });
+        self
+    }
+    /// Add a queue using the default options and no arguments
+    pub fn queue(mut self, name: &str) -> Self {
+        self.queues.push(Queue {
+            name: name.to_owned(),
+            options: QueueDeclareOptions::default(),
+            arguments: FieldTable::default(),
+        });
+        self
+    }
+    /// Add a binding using the default options and no arguments
+    pub fn binding(mut self, queue: &str, exchange: &str, routing_key: &str) -> Self {
+        self.bindings.push(Binding {
+            queue: queue.to_owned(),
+            exchange: exchange.to_owned(),
+            routing_key: routing_key.to_owned(),
+            options: QueueBindOptions::default(),
+            arguments: FieldTable::default(),
+        });
+        self
+    }
+    /// Returns `true` if there is nothing to declare
+    pub fn is_empty(&self) -> bool {
+        self.exchanges.is_empty() && self.queues.is_empty() && self.bindings.is_empty()
+    }
+    /// Declare all exchanges, queues and bindings using the given channel
+    pub async fn declare(&self, channel: &Channel) -> Result<(), Error> {
+        for exchange in &self.exchanges {
+            channel
+                .exchange_declare(
+                    &exchange.name,
+                    exchange.kind.clone(),
+                    exchange.options,
+                    exchange.arguments.clone(),
+                )
+                .await?;
+        }
+        for queue in &self.queues {
+            channel
+                .queue_declare(&queue.name, queue.options, queue.arguments.clone())
+                .await?;
+        }
+        for binding in &self.bindings {
+            channel
+                .queue_bind(
+                    &binding.queue,
+                    &binding.exchange,
+                    &binding.routing_key,
+                    binding.options,
+                    binding.arguments.clone(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+    /// Declare all exchanges, queues and bindings using a temporary channel
+    /// of the given connection
+    pub(crate) async fn declare_on(&self, connection: &Connection) -> Result<(), Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let channel = connection.create_channel().await?;
+        self.declare(&channel).await?;
+        channel.close(REPLY_SUCCESS, "OK").await?;
+        Ok(())
+    }
+}
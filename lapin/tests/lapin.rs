@@ -0,0 +1,134 @@
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use deadpool_lapin::lapin::{
+    options::{BasicPublishOptions, QueueDeclareOptions},
+    types::FieldTable,
+    BasicProperties, ConnectionProperties,
+};
+use deadpool_lapin::{ChannelManager, ChannelPool, Manager, Pool, Topology};
+
+fn addr() -> String {
+    env::var("AMQP_URL")
+        .or_else(|_| env::var("AMQP_ADDR"))
+        .unwrap_or_else(|_| "amqp://127.0.0.1:5672/%2f".to_string())
+}
+
+fn create_pool() -> Pool {
+    let mgr = Manager::new(addr(), ConnectionProperties::default());
+    Pool::new(mgr, 16)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool();
+    let connection = pool.get().await.unwrap();
+    let channel = connection.create_channel().await.unwrap();
+    channel
+        .basic_publish(
+            "",
+            "deadpool_test_basic",
+            BasicPublishOptions::default(),
+            b"hello from deadpool".to_vec(),
+            BasicProperties::default(),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::main]
+#[test]
+async fn test_recycle() {
+    let mgr = Manager::new(addr(), ConnectionProperties::default());
+    let metrics = mgr.metrics();
+    let pool = Pool::new(mgr, 16);
+    for _ in 0..3 {
+        let connection = pool.get().await.unwrap();
+        assert!(connection.status().connected());
+    }
+    assert_eq!(metrics.stats().connections, 1);
+}
+
+#[tokio::main]
+#[test]
+async fn test_topology() {
+    let topology = Topology::new().queue("deadpool_test_topology");
+    let mgr = Manager::new(addr(), ConnectionProperties::default()).with_topology(topology);
+    let pool = Pool::new(mgr, 16);
+    let connection = pool.get().await.unwrap();
+    let channel = connection.create_channel().await.unwrap();
+    // A passive declaration fails if the queue does not exist
+    channel
+        .queue_declare(
+            "deadpool_test_topology",
+            QueueDeclareOptions {
+                passive: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::main]
+#[test]
+async fn test_channel_pool() {
+    let mgr = ChannelManager::new(addr(), ConnectionProperties::default(), 1);
+    let metrics = mgr.metrics();
+    let pool = ChannelPool::new(mgr, 16);
+    let channels = vec![
+        pool.get().await.unwrap(),
+        pool.get().await.unwrap(),
+        pool.get().await.unwrap(),
+    ];
+    assert!(channels.iter().all(|channel| channel.status().connected()));
+    let stats = metrics.stats();
+    assert_eq!(stats.connections, 1);
+    assert_eq!(stats.channels, 3);
+}
+
+#[tokio::main]
+#[test]
+async fn test_channel_pool_dropped_while_connecting() {
+    let first = Arc::new(AtomicBool::new(true));
+    let mgr = ChannelManager::new(addr(), ConnectionProperties::default(), 1).with_post_create(
+        move |_| {
+            let first = first.swap(false, Ordering::SeqCst);
+            Box::pin(async move {
+                if first {
+                    futures::future::pending::<()>().await;
+                }
+                Ok(())
+            })
+        },
+    );
+    let pool = ChannelPool::new(mgr, 16);
+    // drop the future while the only shared connection is being created
+    assert!(tokio::time::timeout(Duration::from_millis(500), pool.get())
+        .await
+        .is_err());
+    let channel = tokio::time::timeout(Duration::from_secs(5), pool.get())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(channel.status().connected());
+}
+
+#[tokio::main]
+#[test]
+async fn test_shutdown() {
+    let mgr = Manager::new(addr(), ConnectionProperties::default());
+    let metrics = mgr.metrics();
+    let shutdown = mgr.shutdown_handle();
+    let pool = Pool::new(mgr, 16);
+    drop(pool.get().await.unwrap());
+    assert_eq!(metrics.stats().connections, 1);
+    shutdown.close(&pool, Duration::from_secs(5)).await;
+    assert!(shutdown.is_closing());
+    assert_eq!(metrics.stats().connections, 0);
+    assert!(pool.get().await.is_err());
+}
@@ -0,0 +1,108 @@
+use deadpool::PoolConfig;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use serde::Deserialize;
+
+use crate::{Error, Manager, Pool};
+
+/// This enum is used to configure how TLS is used. See
+/// `lettre::transport::smtp::client::Tls`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum TlsMode {
+    /// Do not use TLS
+    None,
+    /// Use `STARTTLS` if the server supports it
+    Opportunistic,
+    /// Use `STARTTLS` and fail if the server does not support it
+    Required,
+    /// Connect using TLS right away (SMTPS)
+    Wrapper,
+}
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+///
+/// # Example (reading from the environment)
+///
+/// ```rust
+/// use deadpool_lettre::Config;
+///
+/// std::env::set_var("SMTP__HOST", "mail.example.com");
+/// std::env::set_var("SMTP__MAX_MESSAGES", "100");
+/// std::env::set_var("SMTP__POOL__MAX_SIZE", "8");
+/// let cfg = Config::from_env("SMTP").unwrap();
+/// assert_eq!(cfg.host, Some("mail.example.com".to_string()));
+/// assert_eq!(cfg.pool.unwrap().max_size, 8);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// Host name of the SMTP server. Defaults to `localhost`.
+    pub host: Option<String>,
+    /// Port of the SMTP server. Defaults to `587` when using `STARTTLS`,
+    /// `465` when using `TlsMode::Wrapper` and `25` otherwise.
+    pub port: Option<u16>,
+    /// How TLS is used. Defaults to `TlsMode::Required`.
+    pub tls: Option<TlsMode>,
+    /// User for authentication
+    pub user: Option<String>,
+    /// Password for authentication
+    pub password: Option<String>,
+    /// See `Manager::with_max_messages`
+    pub max_messages: Option<usize>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `SMTP__HOST`, `SMTP__PORT` and `SMTP__POOL__MAX_SIZE`
+    /// for the prefix `SMTP`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create manager using this configuration object. An error is returned
+    /// if the TLS parameters can not be created.
+    pub fn create_manager(&self) -> Result<Manager, Error> {
+        let host = self.host.as_deref().unwrap_or("localhost");
+        let mode = self.tls.unwrap_or(TlsMode::Required);
+        let (tls, default_port) = match mode {
+            TlsMode::None => (Tls::None, 25),
+            TlsMode::Opportunistic => {
+                (Tls::Opportunistic(TlsParameters::new(host.to_owned())?), 25)
+            }
+            TlsMode::Required => (
+                Tls::Required(TlsParameters::new(host.to_owned())?),
+                crate::SUBMISSION_PORT,
+            ),
+            TlsMode::Wrapper => (Tls::Wrapper(TlsParameters::new(host.to_owned())?), 465),
+        };
+        let mut manager = Manager::new(host, self.port.unwrap_or(default_port)).with_tls(tls);
+        if let Some(user) = &self.user {
+            manager = manager.with_credentials(Credentials::new(
+                user.clone(),
+                self.password.clone().unwrap_or_default(),
+            ));
+        }
+        if let Some(max_messages) = self.max_messages {
+            manager = manager.with_max_messages(max_messages);
+        }
+        Ok(manager)
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Result<Pool, Error> {
+        Ok(Pool::from_config(
+            self.create_manager()?,
+            self.pool.clone().unwrap_or_default(),
+        ))
+    }
+}
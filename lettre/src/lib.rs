@@ -0,0 +1,208 @@
+//! Deadpool simple async pool for SMTP connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for the async SMTP connections of
+//! [`lettre`](https://crates.io/crates/lettre).
+//!
+//! Connections are kept alive between messages. They are checked using the
+//! `NOOP` command when they are recycled and can be rotated after a given
+//! number of messages because many servers limit the number of messages
+//! per connection.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_lettre::{Manager, Pool};
+//! use lettre::Message;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::new("127.0.0.1", 25).with_max_messages(100);
+//!     let pool = Pool::new(mgr, 4);
+//!     let message = Message::builder()
+//!         .from("Deadpool <deadpool@example.com>".parse().unwrap())
+//!         .to("Test <test@example.com>".parse().unwrap())
+//!         .subject("Hello")
+//!         .body("Hello world!")
+//!         .unwrap();
+//!     let mut conn = pool.get().await.unwrap();
+//!     conn.send(&message).await.unwrap();
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::fmt;
+
+use async_trait::async_trait;
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::client::{AsyncSmtpConnection, Tls, TlsParameters};
+use lettre::transport::smtp::extension::ClientId;
+use lettre::transport::smtp::response::Response;
+use lettre::Message;
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::{Config, TlsMode};
+
+/// A type alias for using `deadpool::Pool` with `lettre`
+pub type Pool = deadpool::Pool<Connection, Error>;
+
+/// A type alias for using `deadpool::Object` with `lettre`
+pub type Object = deadpool::Object<Connection, Error>;
+
+/// Default port for SMTP submission using `STARTTLS`
+pub const SUBMISSION_PORT: u16 = 587;
+
+/// Authentication mechanisms which are tried in this order
+const MECHANISMS: &[Mechanism] = &[Mechanism::Plain, Mechanism::Login];
+
+/// This error is returned by the `Manager` and `Connection::send`
+#[derive(Debug)]
+pub enum Error {
+    /// The SMTP connection returned an error
+    Smtp(lettre::transport::smtp::Error),
+    /// The server did not answer the `NOOP` command
+    NotConnected,
+    /// The connection has sent the maximum number of messages and is
+    /// replaced by a new one
+    MaxMessagesReached,
+}
+
+impl From<lettre::transport::smtp::Error> for Error {
+    fn from(e: lettre::transport::smtp::Error) -> Self {
+        Error::Smtp(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Smtp(e) => write!(f, "{}", e),
+            Error::NotConnected => write!(f, "Connection to the SMTP server was lost"),
+            Error::MaxMessagesReached => {
+                write!(f, "Connection has sent the maximum number of messages")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The manager for creating and recyling SMTP connections
+pub struct Manager {
+    host: String,
+    port: u16,
+    tls: Tls,
+    hello_name: ClientId,
+    credentials: Option<Credentials>,
+    max_messages: Option<usize>,
+}
+
+impl Manager {
+    /// Create manager for unencrypted connections to the given SMTP server
+    pub fn new(host: &str, port: u16) -> Self {
+        Manager {
+            host: host.to_owned(),
+            port,
+            tls: Tls::None,
+            hello_name: ClientId::default(),
+            credentials: None,
+            max_messages: None,
+        }
+    }
+    /// Create manager for connections to the submission port of the given
+    /// SMTP server which require `STARTTLS`
+    pub fn starttls(host: &str) -> Result<Self, Error> {
+        let tls_parameters = TlsParameters::new(host.to_owned())?;
+        Ok(Self::new(host, SUBMISSION_PORT).with_tls(Tls::Required(tls_parameters)))
+    }
+    /// Set how TLS is used. See `lettre::transport::smtp::client::Tls`.
+    pub fn with_tls(mut self, tls: Tls) -> Self {
+        self.tls = tls;
+        self
+    }
+    /// Set the name which is sent with the `EHLO` command
+    pub fn with_hello_name(mut self, hello_name: ClientId) -> Self {
+        self.hello_name = hello_name;
+        self
+    }
+    /// Authenticate using the given credentials
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+    /// Replace connections after they have sent the given number of
+    /// messages
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Connection, Error> for Manager {
+    async fn create(&self) -> Result<Connection, Error> {
+        let wrapper = match &self.tls {
+            Tls::Wrapper(tls_parameters) => Some(tls_parameters.clone()),
+            _ => None,
+        };
+        let mut conn =
+            AsyncSmtpConnection::connect_tokio02(&self.host, self.port, &self.hello_name, wrapper)
+                .await?;
+        match &self.tls {
+            Tls::Opportunistic(tls_parameters) if conn.can_starttls() => {
+                conn.starttls(tls_parameters.clone(), &self.hello_name)
+                    .await?
+            }
+            Tls::Required(tls_parameters) => {
+                conn.starttls(tls_parameters.clone(), &self.hello_name)
+                    .await?
+            }
+            _ => {}
+        }
+        if let Some(credentials) = &self.credentials {
+            conn.auth(MECHANISMS, credentials).await?;
+        }
+        Ok(Connection { conn, sent: 0 })
+    }
+    async fn recycle(&self, conn: &mut Connection) -> Result<(), Error> {
+        if let Some(max_messages) = self.max_messages {
+            if conn.sent >= max_messages {
+                // Say goodbye politely. The connection is dropped anyways.
+                let _ = conn.conn.quit().await;
+                return Err(Error::MaxMessagesReached);
+            }
+        }
+        // `test_connected` sends a `NOOP` command
+        if !conn.conn.test_connected().await {
+            return Err(Error::NotConnected);
+        }
+        Ok(())
+    }
+}
+
+/// An SMTP connection which counts the messages it has sent
+pub struct Connection {
+    conn: AsyncSmtpConnection,
+    sent: usize,
+}
+
+impl Connection {
+    /// Send the given message using its envelope
+    pub async fn send(&mut self, message: &Message) -> Result<Response, Error> {
+        let response = self
+            .conn
+            .send(message.envelope(), &message.formatted())
+            .await?;
+        self.sent += 1;
+        Ok(response)
+    }
+    /// Number of messages which were sent using this connection
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+}
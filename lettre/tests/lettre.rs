@@ -0,0 +1,45 @@
+use std::env;
+
+use deadpool_lettre::{Manager, Pool};
+use lettre::Message;
+
+fn create_pool(max_messages: usize) -> Pool {
+    let host = env::var("SMTP_HOST").unwrap_or_else(|_| "127.0.0.1".into());
+    let port = env::var("SMTP_PORT")
+        .map(|port| port.parse().unwrap())
+        .unwrap_or(1025);
+    Pool::new(Manager::new(&host, port).with_max_messages(max_messages), 1)
+}
+
+fn message() -> Message {
+    Message::builder()
+        .from("Deadpool <deadpool@example.com>".parse().unwrap())
+        .to("Test <test@example.com>".parse().unwrap())
+        .subject("Hello")
+        .body("Hello world!")
+        .unwrap()
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool(10);
+    let mut conn = pool.get().await.unwrap();
+    conn.send(&message()).await.unwrap();
+    drop(conn);
+    let mut conn = pool.get().await.unwrap();
+    conn.send(&message()).await.unwrap();
+    assert_eq!(conn.sent(), 2);
+}
+
+#[tokio::main]
+#[test]
+async fn test_max_messages() {
+    let pool = create_pool(1);
+    let mut conn = pool.get().await.unwrap();
+    conn.send(&message()).await.unwrap();
+    assert_eq!(conn.sent(), 1);
+    drop(conn);
+    let conn = pool.get().await.unwrap();
+    assert_eq!(conn.sent(), 0);
+}
@@ -0,0 +1,24 @@
+use std::env;
+
+use deadpool_mongodb::{Manager, Pool};
+use mongodb::bson::doc;
+
+async fn create_pool() -> Pool {
+    let url = env::var("MONGODB_URL").unwrap_or_else(|_| "mongodb://127.0.0.1:27017".into());
+    Pool::new(Manager::from_uri(&url).await.unwrap(), 16)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool().await;
+    let client = pool.get().await.unwrap();
+    let collection = client.database("deadpool").collection("test_basic");
+    collection.delete_many(doc! {}, None).await.unwrap();
+    collection
+        .insert_one(doc! { "value": 42 }, None)
+        .await
+        .unwrap();
+    let document = collection.find_one(None, None).await.unwrap().unwrap();
+    assert_eq!(document.get_i32("value").unwrap(), 42);
+}
@@ -0,0 +1,189 @@
+//! Deadpool simple async pool for MySQL and MariaDB connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`mysql_async`](https://crates.io/crates/mysql_async) and
+//! provides a wrapper with methods for executing queries via the statement
+//! cache of `mysql_async`.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_mysql::{Manager, Pool};
+//! use mysql_async::Opts;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let opts = Opts::from_url("mysql://root@127.0.0.1/deadpool").unwrap();
+//!     let mgr = Manager::new(opts);
+//!     let pool = Pool::new(mgr, 16);
+//!     for i in 1..10i32 {
+//!         let mut client = pool.get().await.unwrap();
+//!         let value: Option<i32> = client
+//!             .exec_first_cached("SELECT 1 + ?", (i,))
+//!             .await
+//!             .unwrap();
+//!         assert_eq!(value, Some(i + 1));
+//!     }
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::ops::{Deref, DerefMut};
+
+use async_trait::async_trait;
+use mysql_async::prelude::{FromRow, Queryable};
+use mysql_async::{Conn, Error, Opts, OptsBuilder, Params, Statement};
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with `mysql_async`
+pub type Pool = deadpool::Pool<Client, Error>;
+
+/// A type alias for using `deadpool::Object` with `mysql_async`
+pub type Object = deadpool::Object<Client, Error>;
+
+/// This enum is used to control how the connection is checked when it is
+/// recycled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+pub enum RecyclingMethod {
+    /// Do not check the connection at all. This does not cause any network
+    /// round trip but does not detect broken connections.
+    Fast,
+    /// Send a `COM_PING` to check if the connection is still usable. This
+    /// is the default.
+    Verified,
+    /// Reset the session state via `COM_RESET_CONNECTION`. This rolls back
+    /// open transactions, drops temporary tables, releases locks and
+    /// deallocates all prepared statements.
+    Clean,
+}
+
+impl Default for RecyclingMethod {
+    fn default() -> Self {
+        RecyclingMethod::Verified
+    }
+}
+
+/// Configuration object for the `Manager`
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct ManagerConfig {
+    /// Method of how a connection is recycled. See `RecyclingMethod`.
+    pub recycling_method: RecyclingMethod,
+    /// Maximum number of statements cached per connection. This sets the
+    /// `stmt_cache_size` of `mysql_async` which closes the least recently
+    /// used statement when the cache is full. `None` keeps the value of
+    /// the `Opts`, which defaults to 10.
+    pub statement_cache_size: Option<usize>,
+}
+
+/// The manager for creating and recyling MySQL connections
+pub struct Manager {
+    opts: Opts,
+    config: ManagerConfig,
+}
+
+impl Manager {
+    /// Create manager using the given `mysql_async::Opts`
+    pub fn new(opts: Opts) -> Self {
+        Self::from_config(opts, ManagerConfig::default())
+    }
+    /// Create manager using the given `mysql_async::Opts` and
+    /// `ManagerConfig`
+    pub fn from_config(opts: Opts, config: ManagerConfig) -> Self {
+        Self { opts, config }
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Client, Error> for Manager {
+    async fn create(&self) -> Result<Client, Error> {
+        let mut opts = OptsBuilder::from_opts(self.opts.clone());
+        if let Some(size) = self.config.statement_cache_size {
+            opts = opts.stmt_cache_size(size);
+        }
+        let conn = Conn::new(opts).await?;
+        Ok(Client { conn })
+    }
+    async fn recycle(&self, client: &mut Client) -> Result<(), Error> {
+        match self.config.recycling_method {
+            RecyclingMethod::Fast => {}
+            RecyclingMethod::Verified => client.conn.ping().await?,
+            RecyclingMethod::Clean => client.conn.reset().await?,
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper for `mysql_async::Conn` with methods for preparing and
+/// executing statements via the statement cache of the connection.
+///
+/// The statements are cached by `mysql_async` itself, see
+/// `ManagerConfig::statement_cache_size`.
+pub struct Client {
+    conn: Conn,
+}
+
+impl Client {
+    /// Creates a new prepared statement using the statement cache if
+    /// possible.
+    ///
+    /// See [`mysql_async::prelude::Queryable::prep`](https://docs.rs/mysql_async/0.24/mysql_async/prelude/trait.Queryable.html#tymethod.prep)
+    pub async fn prep_cached(&mut self, query: &str) -> Result<Statement, Error> {
+        self.conn.prep(query).await
+    }
+    /// Prepare the statement using the statement cache and execute it
+    /// returning all rows.
+    pub async fn exec_cached<T, P>(&mut self, query: &str, params: P) -> Result<Vec<T>, Error>
+    where
+        T: FromRow + Send + 'static,
+        P: Into<Params> + Send,
+    {
+        let statement = self.prep_cached(query).await?;
+        self.conn.exec(&statement, params).await
+    }
+    /// Prepare the statement using the statement cache and execute it
+    /// returning the first row.
+    pub async fn exec_first_cached<T, P>(
+        &mut self,
+        query: &str,
+        params: P,
+    ) -> Result<Option<T>, Error>
+    where
+        T: FromRow + Send + 'static,
+        P: Into<Params> + Send,
+    {
+        let statement = self.prep_cached(query).await?;
+        self.conn.exec_first(&statement, params).await
+    }
+    /// Prepare the statement using the statement cache and execute it
+    /// dropping the result.
+    pub async fn exec_drop_cached<P>(&mut self, query: &str, params: P) -> Result<(), Error>
+    where
+        P: Into<Params> + Send,
+    {
+        let statement = self.prep_cached(query).await?;
+        self.conn.exec_drop(&statement, params).await
+    }
+}
+
+impl Deref for Client {
+    type Target = Conn;
+    fn deref(&self) -> &Conn {
+        &self.conn
+    }
+}
+
+impl DerefMut for Client {
+    fn deref_mut(&mut self) -> &mut Conn {
+        &mut self.conn
+    }
+}
@@ -0,0 +1,71 @@
+use std::env;
+
+use deadpool_mysql::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use mysql_async::Opts;
+
+fn create_pool(manager_config: ManagerConfig) -> Pool {
+    let url = env::var("MYSQL_URL").unwrap_or_else(|_| "mysql://root@127.0.0.1/deadpool".into());
+    let opts = Opts::from_url(&url).unwrap();
+    Pool::new(Manager::from_config(opts, manager_config), 16)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool(ManagerConfig::default());
+    let mut client = pool.get().await.unwrap();
+    let value: Option<i32> = client
+        .exec_first_cached("SELECT 1 + ?", (2,))
+        .await
+        .unwrap();
+    assert_eq!(value, Some(3));
+}
+
+#[tokio::main]
+#[test]
+async fn test_statement_cache_size() {
+    let pool = create_pool(ManagerConfig {
+        statement_cache_size: Some(2),
+        ..Default::default()
+    });
+    let mut client = pool.get().await.unwrap();
+    // More distinct queries than fit into the cache, used repeatedly so
+    // evicted statements are prepared again.
+    for _ in 0..2 {
+        for i in 0..12i32 {
+            let query = format!("SELECT {} + ?", i);
+            let value: Option<i32> = client.exec_first_cached(&query, (1,)).await.unwrap();
+            assert_eq!(value, Some(i + 1));
+        }
+    }
+}
+
+#[tokio::main]
+#[test]
+async fn test_statement_cache_default_size() {
+    let pool = create_pool(ManagerConfig::default());
+    let mut client = pool.get().await.unwrap();
+    // `mysql_async` caches 10 statements by default
+    for _ in 0..2 {
+        for i in 0..12i32 {
+            let query = format!("SELECT {} + ?", i);
+            let value: Option<i32> = client.exec_first_cached(&query, (1,)).await.unwrap();
+            assert_eq!(value, Some(i + 1));
+        }
+    }
+}
+
+#[tokio::main]
+#[test]
+async fn test_recycling_method_clean() {
+    let pool = create_pool(ManagerConfig {
+        recycling_method: RecyclingMethod::Clean,
+        ..Default::default()
+    });
+    let mut client = pool.get().await.unwrap();
+    client.prep_cached("SELECT 1").await.unwrap();
+    drop(client);
+    let mut client = pool.get().await.unwrap();
+    let value: Option<i32> = client.exec_first_cached("SELECT 1", ()).await.unwrap();
+    assert_eq!(value, Some(1));
+}
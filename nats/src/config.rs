@@ -0,0 +1,77 @@
+use deadpool::PoolConfig;
+use serde::Deserialize;
+
+use crate::{Manager, Pool};
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+///
+/// # Example (reading from the environment)
+///
+/// ```rust
+/// use deadpool_nats::Config;
+///
+/// std::env::set_var("NATS__NAME", "deadpool");
+/// std::env::set_var("NATS__POOL__MAX_SIZE", "8");
+/// let cfg = Config::from_env("NATS").unwrap();
+/// assert_eq!(cfg.name, Some("deadpool".to_string()));
+/// assert_eq!(cfg.pool.unwrap().max_size, 8);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// URLs of the servers. Defaults to `nats://127.0.0.1:4222`.
+    pub servers: Option<Vec<String>>,
+    /// See `Manager::with_name`
+    pub name: Option<String>,
+    /// See `Manager::with_user_pass`
+    pub user: Option<String>,
+    /// See `Manager::with_user_pass`
+    pub password: Option<String>,
+    /// See `Manager::with_token`
+    pub token: Option<String>,
+    /// See `Manager::with_max_reconnects`
+    pub max_reconnects: Option<usize>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `NATS__NAME` and `NATS__POOL__MAX_SIZE` for the prefix
+    /// `NATS`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create manager using this configuration object
+    pub fn create_manager(&self) -> Manager {
+        let servers = self
+            .servers
+            .clone()
+            .unwrap_or_else(|| vec!["nats://127.0.0.1:4222".to_string()]);
+        let mut manager = Manager::new(servers).with_max_reconnects(self.max_reconnects);
+        if let Some(name) = &self.name {
+            manager = manager.with_name(name);
+        }
+        if let Some(user) = &self.user {
+            manager = manager.with_user_pass(user, self.password.as_deref().unwrap_or_default());
+        }
+        if let Some(token) = &self.token {
+            manager = manager.with_token(token);
+        }
+        manager
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Pool {
+        Pool::from_config(self.create_manager(), self.pool.clone().unwrap_or_default())
+    }
+}
@@ -0,0 +1,165 @@
+//! Deadpool simple async pool for NATS connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`async-nats`](https://crates.io/crates/async-nats).
+//!
+//! Every connection knows all servers of the cluster and fails over to
+//! the next server if the current one becomes unavailable. Pooling the
+//! connections gives control over the number of connections which are
+//! open at the same time.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_nats::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::new(vec!["nats://127.0.0.1:4222".to_string()])
+//!         .with_reconnect_callback(|| println!("reconnected"));
+//!     let pool = Pool::new(mgr, 4);
+//!     let conn = pool.get().await.unwrap();
+//!     conn.publish("deadpool", "Hello world!").await.unwrap();
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::io::Error;
+use std::sync::Arc;
+
+use async_nats::{Connection, Options};
+use async_trait::async_trait;
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with `async-nats`
+pub type Pool = deadpool::Pool<Connection, Error>;
+
+/// A type alias for using `deadpool::Object` with `async-nats`
+pub type Object = deadpool::Object<Connection, Error>;
+
+/// Callback which is called when a connection created by the manager is
+/// disconnected, reconnected or closed. See
+/// `Manager::with_disconnect_callback`, `Manager::with_reconnect_callback`
+/// and `Manager::with_close_callback`.
+pub type Callback = Arc<dyn Fn() + Send + Sync>;
+
+/// The manager for creating and recyling NATS connections
+pub struct Manager {
+    servers: Vec<String>,
+    name: Option<String>,
+    credentials: Option<(String, String)>,
+    token: Option<String>,
+    max_reconnects: Option<usize>,
+    disconnect_callback: Option<Callback>,
+    reconnect_callback: Option<Callback>,
+    close_callback: Option<Callback>,
+}
+
+impl Manager {
+    /// Create manager using the URLs of one or more servers (e.g.
+    /// `nats://127.0.0.1:4222`). If the current server of a connection
+    /// becomes unavailable the connection fails over to the other servers.
+    pub fn new(servers: Vec<String>) -> Self {
+        Manager {
+            servers,
+            name: None,
+            credentials: None,
+            token: None,
+            max_reconnects: None,
+            disconnect_callback: None,
+            reconnect_callback: None,
+            close_callback: None,
+        }
+    }
+    /// Set the name of the connections which is reported to the server
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+    /// Authenticate using the given user and password
+    pub fn with_user_pass(mut self, user: &str, password: &str) -> Self {
+        self.credentials = Some((user.to_owned(), password.to_owned()));
+        self
+    }
+    /// Authenticate using the given token
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_owned());
+        self
+    }
+    /// Set the maximum number of reconnection attempts per server before a
+    /// connection is closed. `None` uses the default of `async-nats`.
+    pub fn with_max_reconnects(mut self, max_reconnects: Option<usize>) -> Self {
+        self.max_reconnects = max_reconnects;
+        self
+    }
+    /// Set a callback which is called whenever a connection created by this
+    /// manager loses the connection to its server
+    pub fn with_disconnect_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.disconnect_callback = Some(Arc::new(callback));
+        self
+    }
+    /// Set a callback which is called whenever a connection created by this
+    /// manager has reconnected to a server
+    pub fn with_reconnect_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.reconnect_callback = Some(Arc::new(callback));
+        self
+    }
+    /// Set a callback which is called whenever a connection created by this
+    /// manager is closed because no server could be reached. The
+    /// connection is discarded when it is recycled the next time.
+    pub fn with_close_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.close_callback = Some(Arc::new(callback));
+        self
+    }
+    fn options(&self) -> Options {
+        let mut options = match (&self.credentials, &self.token) {
+            (Some((user, password)), _) => Options::with_user_pass(user, password),
+            (None, Some(token)) => Options::with_token(token),
+            (None, None) => Options::new(),
+        };
+        if let Some(name) = &self.name {
+            options = options.with_name(name);
+        }
+        if self.max_reconnects.is_some() {
+            options = options.max_reconnects(self.max_reconnects);
+        }
+        if let Some(callback) = self.disconnect_callback.clone() {
+            options = options.disconnect_callback(move || callback());
+        }
+        if let Some(callback) = self.reconnect_callback.clone() {
+            options = options.reconnect_callback(move || callback());
+        }
+        if let Some(callback) = self.close_callback.clone() {
+            options = options.close_callback(move || callback());
+        }
+        options
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Connection, Error> for Manager {
+    async fn create(&self) -> Result<Connection, Error> {
+        self.options().connect(&self.servers.join(",")).await
+    }
+    async fn recycle(&self, conn: &mut Connection) -> Result<(), Error> {
+        // Flushing sends a PING and waits for the PONG of the server so
+        // closed and stale connections are detected.
+        conn.flush().await
+    }
+}
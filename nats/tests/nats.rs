@@ -0,0 +1,29 @@
+use std::env;
+
+use deadpool_nats::{Manager, Pool};
+
+fn create_pool() -> Pool {
+    let url = env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".into());
+    Pool::new(Manager::new(vec![url]).with_name("deadpool"), 4)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool();
+    let conn = pool.get().await.unwrap();
+    let sub = conn.subscribe("deadpool.test").await.unwrap();
+    conn.publish("deadpool.test", "42").await.unwrap();
+    let msg = sub.next().await.unwrap();
+    assert_eq!(msg.data, b"42");
+}
+
+#[tokio::main]
+#[test]
+async fn test_recycle() {
+    let pool = create_pool();
+    let conn = pool.get().await.unwrap();
+    drop(conn);
+    let conn = pool.get().await.unwrap();
+    conn.flush().await.unwrap();
+}
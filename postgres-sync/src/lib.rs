@@ -0,0 +1,218 @@
+//! Deadpool simple async pool for blocking PostgreSQL connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for the blocking [`postgres`](https://crates.io/crates/postgres)
+//! client. Connections are created, recycled and used on the blocking
+//! thread pool of `tokio` so they never block the async executor.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::env;
+//!
+//! use deadpool_postgres_sync::{Manager, Pool};
+//! use postgres::{Config, NoTls};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut cfg = Config::new();
+//!     cfg.host("/var/run/postgresql");
+//!     cfg.user(env::var("USER").unwrap().as_str());
+//!     cfg.dbname("deadpool");
+//!     let mgr = Manager::new(cfg, NoTls);
+//!     let pool = Pool::new(mgr, 16);
+//!     for i in 1..10 {
+//!         let client = pool.get().await.unwrap();
+//!         let value: i32 = client
+//!             .interact(move |client| {
+//!                 let stmt = client.prepare_cached("SELECT 1 + $1")?;
+//!                 let rows = client.query(&stmt, &[&i])?;
+//!                 Ok::<_, postgres::Error>(rows[0].get(0))
+//!             })
+//!             .await
+//!             .unwrap()
+//!             .unwrap();
+//!         assert_eq!(value, i + 1);
+//!     }
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use postgres::{
+    tls::{MakeTlsConnect, TlsConnect},
+    Client as PgClient, Config as PgConfig, Socket, Statement,
+};
+use tokio::runtime::Handle;
+use tokio::task::spawn_blocking;
+
+/// A type alias for using `deadpool::Pool` with `postgres`
+pub type Pool = deadpool::Pool<Client, Error>;
+
+/// This error is returned by the `Manager` and `Client::interact`
+#[derive(Debug)]
+pub enum Error {
+    /// The `postgres` client returned an error
+    Backend(postgres::Error),
+    /// The closure passed to `Client::interact` panicked
+    Panic,
+    /// The connection is unusable because a previous interaction panicked
+    Poisoned,
+}
+
+impl From<postgres::Error> for Error {
+    fn from(e: postgres::Error) -> Self {
+        Error::Backend(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Backend(e) => write!(f, "{}", e),
+            Error::Panic => write!(f, "Interaction with the connection panicked"),
+            Error::Poisoned => write!(f, "Connection is poisoned by a previous panic"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A wrapper for `postgres::Client` which includes a statement cache.
+///
+/// This object is passed to the closure of `Client::interact`.
+pub struct SyncClient {
+    client: PgClient,
+    statement_cache: HashMap<String, Statement>,
+}
+
+impl SyncClient {
+    /// Creates a new prepared statement using the statement cache if possible.
+    ///
+    /// See [`postgres::Client::prepare`](#method.prepare)
+    pub fn prepare_cached(&mut self, query: &str) -> Result<Statement, postgres::Error> {
+        match self.statement_cache.get(query) {
+            Some(statement) => Ok(statement.clone()),
+            None => {
+                let stmt = self.client.prepare(query)?;
+                self.statement_cache.insert(query.to_owned(), stmt.clone());
+                Ok(stmt)
+            }
+        }
+    }
+    /// Retrieve current size of the statement cache
+    pub fn statement_cache_size(&self) -> usize {
+        self.statement_cache.len()
+    }
+    /// Clear the statement cache
+    pub fn clear_statement_cache(&mut self) {
+        self.statement_cache.clear();
+    }
+}
+
+impl Deref for SyncClient {
+    type Target = PgClient;
+    fn deref(&self) -> &PgClient {
+        &self.client
+    }
+}
+
+impl DerefMut for SyncClient {
+    fn deref_mut(&mut self) -> &mut PgClient {
+        &mut self.client
+    }
+}
+
+/// The pooled object which provides access to the blocking client via
+/// `Client::interact`.
+pub struct Client {
+    /// This is only `None` while the client is being dropped
+    inner: Option<Arc<Mutex<SyncClient>>>,
+}
+
+impl Client {
+    fn new(client: PgClient) -> Self {
+        Client {
+            inner: Some(Arc::new(Mutex::new(SyncClient {
+                client,
+                statement_cache: HashMap::new(),
+            }))),
+        }
+    }
+    /// Run the given closure on the blocking thread pool passing it the
+    /// wrapped client and return its result.
+    ///
+    /// If the closure panics `Error::Panic` is returned. The object is still
+    /// returned to the pool, but the panic poisons the connection so the
+    /// next `recycle` fails with `Error::Poisoned` and discards it.
+    pub async fn interact<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut SyncClient) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let inner = self.inner.as_ref().unwrap().clone();
+        spawn_blocking(move || match inner.lock() {
+            Ok(mut client) => Ok(f(&mut client)),
+            Err(_) => Err(Error::Poisoned),
+        })
+        .await
+        .map_err(|_| Error::Panic)?
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        // `postgres::Client` owns a runtime which must not be dropped from
+        // within an async context.
+        if let Some(inner) = self.inner.take() {
+            if Handle::try_current().is_ok() {
+                spawn_blocking(move || drop(inner));
+            }
+        }
+    }
+}
+
+/// The manager for creating and recyling blocking postgresql connections
+pub struct Manager<T> {
+    config: PgConfig,
+    tls: T,
+}
+
+impl<T> Manager<T> {
+    /// Create manager using `postgres::Config` and a `TlsConnector`
+    pub fn new(config: PgConfig, tls: T) -> Self {
+        Manager { config, tls }
+    }
+}
+
+#[async_trait]
+impl<T> deadpool::Manager<Client, Error> for Manager<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+    T::Stream: Send,
+    T::TlsConnect: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    async fn create(&self) -> Result<Client, Error> {
+        let config = self.config.clone();
+        let tls = self.tls.clone();
+        let client = spawn_blocking(move || config.connect(tls))
+            .await
+            .map_err(|_| Error::Panic)??;
+        Ok(Client::new(client))
+    }
+    async fn recycle(&self, client: &mut Client) -> Result<(), Error> {
+        client
+            .interact(|client| client.simple_query("").map(|_| ()))
+            .await??;
+        Ok(())
+    }
+}
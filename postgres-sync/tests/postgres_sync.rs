@@ -0,0 +1,56 @@
+use std::env;
+use std::path::Path;
+
+use deadpool_postgres_sync::{Manager, Pool};
+
+fn create_pool() -> Pool {
+    let mut config = postgres::Config::new();
+    if let Ok(host) = env::var("PG_HOST") {
+        config.host(host.as_str());
+    } else if Path::new("/run/postgresql").exists() {
+        config.host("/run/postgresql");
+    } else {
+        config.host("/tmp");
+    }
+    if let Ok(user) = env::var("PG_USER") {
+        config.user(user.as_str());
+    } else {
+        config.user(env::var("USER").unwrap().as_str());
+    }
+    if let Ok(password) = env::var("PG_PASSWORD") {
+        config.password(password.as_str());
+    }
+    config.dbname(&env::var("PG_DBNAME").unwrap_or_else(|_| "deadpool".to_string()));
+    Pool::new(Manager::new(config, postgres::NoTls), 16)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool();
+    let client = pool.get().await.unwrap();
+    let value: i32 = client
+        .interact(|client| {
+            let stmt = client.prepare_cached("SELECT 1 + 2")?;
+            client.prepare_cached("SELECT 1 + 2")?;
+            assert_eq!(client.statement_cache_size(), 1);
+            let rows = client.query(&stmt, &[])?;
+            Ok::<_, postgres::Error>(rows[0].get(0))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value, 3);
+}
+
+#[tokio::main]
+#[test]
+async fn test_panic() {
+    use deadpool_postgres_sync::Error;
+    let pool = create_pool();
+    let client = pool.get().await.unwrap();
+    let result = client.interact(|_| panic!("boom")).await;
+    assert!(matches!(result, Err(Error::Panic)));
+    let result = client.interact(|_| ()).await;
+    assert!(matches!(result, Err(Error::Poisoned)));
+}
@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc;
+use futures::future::{self, BoxFuture};
+use futures::select;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::{info, warn};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_postgres::{error::DbError, AsyncMessage, Connection};
+
+/// Callback which is called for every notice sent by the server. See
+/// `Manager::set_notice_callback`.
+pub type NoticeCallback = Arc<dyn Fn(&DbError) + Send + Sync>;
+
+/// Callback which is called with the name and new value of a parameter
+/// whenever the server reports a change. See
+/// `Manager::set_parameter_status_callback`.
+pub type ParameterStatusCallback = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Parameters which are reported by the server when they change
+const REPORTED_PARAMETERS: &[&str] = &[
+    "application_name",
+    "client_encoding",
+    "DateStyle",
+    "integer_datetimes",
+    "IntervalStyle",
+    "is_superuser",
+    "server_encoding",
+    "server_version",
+    "session_authorization",
+    "standard_conforming_strings",
+    "TimeZone",
+];
+
+/// Drive the connection until it is closed and forward notices and
+/// parameter status changes to the callbacks. Notifications are dropped
+/// as they are only received by a `Listener`.
+pub(crate) async fn run<S, T>(
+    mut connection: Connection<S, T>,
+    notice_callback: Option<NoticeCallback>,
+    parameter_status_callback: Option<ParameterStatusCallback>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut parameters: Vec<Option<String>> = REPORTED_PARAMETERS
+        .iter()
+        .map(|name| connection.parameter(name).map(str::to_owned))
+        .collect();
+    loop {
+        let message = future::poll_fn(|cx| {
+            let message = connection.poll_message(cx);
+            // Parameter status messages are processed by the connection
+            // without being returned as `AsyncMessage`.
+            if let Some(callback) = &parameter_status_callback {
+                for (name, value) in REPORTED_PARAMETERS.iter().zip(parameters.iter_mut()) {
+                    let current = connection.parameter(name);
+                    if current != value.as_deref() {
+                        *value = current.map(str::to_owned);
+                        callback(name, current.unwrap_or_default());
+                    }
+                }
+            }
+            message
+        })
+        .await;
+        match message {
+            Some(Ok(AsyncMessage::Notice(notice))) => match &notice_callback {
+                Some(callback) => callback(&notice),
+                None => info!(
+                    target: "deadpool.postgres",
+                    "{}: {}",
+                    notice.severity(),
+                    notice.message()
+                ),
+            },
+            Some(Ok(_)) => {}
+            Some(Err(e)) => {
+                warn!(target: "deadpool.postgres", "Connection error: {}", e);
+                return;
+            }
+            None => return,
+        }
+    }
+}
+
+/// Drives the connections of a manager from a single task instead of one
+/// task per connection. See `ManagerConfig::shared_driver`.
+#[derive(Default)]
+pub(crate) struct SharedDriver {
+    sender: Mutex<Option<mpsc::UnboundedSender<BoxFuture<'static, ()>>>>,
+}
+
+impl SharedDriver {
+    /// Pass the future driving a connection to the driver task. The task
+    /// is started via `spawn` if it is not running, yet, or if it stopped
+    /// because its runtime was shut down.
+    pub(crate) fn drive<S>(&self, connection: BoxFuture<'static, ()>, spawn: S)
+    where
+        S: FnOnce(BoxFuture<'static, ()>),
+    {
+        let mut sender = self.sender.lock().unwrap();
+        let connection = match &*sender {
+            Some(sender) => match sender.unbounded_send(connection) {
+                Ok(()) => return,
+                Err(e) => e.into_inner(),
+            },
+            None => connection,
+        };
+        let (new_sender, receiver) = mpsc::unbounded();
+        new_sender.unbounded_send(connection).unwrap();
+        spawn(Box::pin(drive_all(receiver)));
+        *sender = Some(new_sender);
+    }
+}
+
+async fn drive_all(mut receiver: mpsc::UnboundedReceiver<BoxFuture<'static, ()>>) {
+    let mut connections = FuturesUnordered::new();
+    loop {
+        select! {
+            connection = receiver.next() => match connection {
+                Some(connection) => connections.push(connection),
+                None => break,
+            },
+            () = connections.select_next_some() => {},
+        }
+    }
+    // The manager was dropped. Keep driving the connections which are
+    // still in use.
+    while connections.next().await.is_some() {}
+}
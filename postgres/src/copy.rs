@@ -0,0 +1,113 @@
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::{Buf, Bytes};
+use futures::{Sink, Stream};
+use tokio_postgres::{
+    binary_copy::BinaryCopyInWriter,
+    types::{ToSql, Type},
+    CopyInSink, CopyOutStream, Error,
+};
+
+/// A sink for `COPY ... FROM STDIN` query data.
+///
+/// This wraps `tokio_postgres::CopyInSink`. It borrows the client, see
+/// `Client`.
+///
+/// The copy must be explicitly completed via `finish`. If it is not, it
+/// will be aborted.
+pub struct CopyIn<'a, T: Buf + 'static + Send> {
+    sink: Pin<Box<CopyInSink<T>>>,
+    _client: PhantomData<&'a mut ()>,
+}
+
+impl<'a, T: Buf + 'static + Send> CopyIn<'a, T> {
+    pub(crate) fn new(sink: CopyInSink<T>) -> Self {
+        CopyIn {
+            sink: Box::pin(sink),
+            _client: PhantomData,
+        }
+    }
+    /// Completes the copy, returning the number of rows inserted.
+    ///
+    /// See [`tokio_postgres::CopyInSink::finish`](https://docs.rs/tokio-postgres/0.5/tokio_postgres/struct.CopyInSink.html#method.finish)
+    pub async fn finish(mut self) -> Result<u64, Error> {
+        self.sink.as_mut().finish().await
+    }
+}
+
+impl<'a, T: Buf + 'static + Send> Sink<T> for CopyIn<'a, T> {
+    type Error = Error;
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        self.sink.as_mut().poll_ready(cx)
+    }
+    fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Error> {
+        self.sink.as_mut().start_send(item)
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        self.sink.as_mut().poll_flush(cx)
+    }
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        self.sink.as_mut().poll_close(cx)
+    }
+}
+
+/// A writer of rows for `COPY ... FROM STDIN (FORMAT binary)` queries.
+///
+/// This wraps `tokio_postgres::binary_copy::BinaryCopyInWriter` which
+/// takes care of the header and trailer of the binary format and encodes
+/// the values of every row using the column types passed on creation. It
+/// borrows the client, see `Client`.
+///
+/// The copy must be explicitly completed via `finish`. If it is not, it
+/// will be aborted.
+pub struct BinaryCopyIn<'a> {
+    writer: Pin<Box<BinaryCopyInWriter>>,
+    _client: PhantomData<&'a mut ()>,
+}
+
+impl<'a> BinaryCopyIn<'a> {
+    pub(crate) fn new(sink: CopyInSink<Bytes>, types: &[Type]) -> Self {
+        BinaryCopyIn {
+            writer: Box::pin(BinaryCopyInWriter::new(sink, types)),
+            _client: PhantomData,
+        }
+    }
+    /// Writes a single row. The number of values must match the number of
+    /// column types.
+    ///
+    /// See [`tokio_postgres::binary_copy::BinaryCopyInWriter::write`](https://docs.rs/tokio-postgres/0.5/tokio_postgres/binary_copy/struct.BinaryCopyInWriter.html#method.write)
+    pub async fn write(&mut self, values: &[&(dyn ToSql + Sync)]) -> Result<(), Error> {
+        self.writer.as_mut().write(values).await
+    }
+    /// Completes the copy, returning the number of rows inserted.
+    pub async fn finish(mut self) -> Result<u64, Error> {
+        self.writer.as_mut().finish().await
+    }
+}
+
+/// A stream of `COPY ... TO STDOUT` query data.
+///
+/// This wraps `tokio_postgres::CopyOutStream`. It borrows the client, see
+/// `Client`.
+pub struct CopyOut<'a> {
+    stream: Pin<Box<CopyOutStream>>,
+    _client: PhantomData<&'a mut ()>,
+}
+
+impl<'a> CopyOut<'a> {
+    pub(crate) fn new(stream: CopyOutStream) -> Self {
+        CopyOut {
+            stream: Box::pin(stream),
+            _client: PhantomData,
+        }
+    }
+}
+
+impl<'a> Stream for CopyOut<'a> {
+    type Item = Result<Bytes, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use tokio_postgres::{
+    types::{ToSql, Type},
+    Error, Row, Statement,
+};
+
+use crate::{Client, Transaction};
+
+/// A trait allowing functions to accept either a `Client` or a
+/// `Transaction` and use the statement cache in both cases.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use deadpool_postgres::GenericClient;
+///
+/// async fn user_count(client: &mut impl GenericClient) -> Result<i64, Error> {
+///     let row = client.query_one_cached("SELECT COUNT(*) FROM users", &[]).await?;
+///     Ok(row.get(0))
+/// }
+/// ```
+#[async_trait]
+pub trait GenericClient: Send + Sync {
+    /// Like `Client::prepare`
+    async fn prepare(&mut self, query: &str) -> Result<Statement, Error>;
+    /// Like `Client::prepare_typed_cached`
+    async fn prepare_typed_cached(
+        &mut self,
+        query: &str,
+        types: &[Type],
+    ) -> Result<Statement, Error>;
+    /// Like `Client::query_cached`
+    async fn query_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error>;
+    /// Like `Client::query_one_cached`
+    async fn query_one_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error>;
+    /// Like `Client::query_opt_cached`
+    async fn query_opt_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>;
+    /// Like `Client::execute_cached`
+    async fn execute_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error>;
+    /// Like `Client::batch_execute`
+    async fn batch_execute(&self, query: &str) -> Result<(), Error>;
+    /// Like `Client::transaction`. Transactions started by a `Transaction`
+    /// are nested using savepoints.
+    async fn transaction(&mut self) -> Result<Transaction<'_>, Error>;
+}
+
+#[async_trait]
+impl GenericClient for Client {
+    async fn prepare(&mut self, query: &str) -> Result<Statement, Error> {
+        Client::prepare(self, query).await
+    }
+    async fn prepare_typed_cached(
+        &mut self,
+        query: &str,
+        types: &[Type],
+    ) -> Result<Statement, Error> {
+        Client::prepare_typed_cached(self, query, types).await
+    }
+    async fn query_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        Client::query_cached(self, query, params).await
+    }
+    async fn query_one_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error> {
+        Client::query_one_cached(self, query, params).await
+    }
+    async fn query_opt_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error> {
+        Client::query_opt_cached(self, query, params).await
+    }
+    async fn execute_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        Client::execute_cached(self, query, params).await
+    }
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Client::batch_execute(self, query).await
+    }
+    async fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+        Client::transaction(self).await
+    }
+}
+
+#[async_trait]
+impl<'a> GenericClient for Transaction<'a> {
+    async fn prepare(&mut self, query: &str) -> Result<Statement, Error> {
+        Transaction::prepare(self, query).await
+    }
+    async fn prepare_typed_cached(
+        &mut self,
+        query: &str,
+        types: &[Type],
+    ) -> Result<Statement, Error> {
+        Transaction::prepare_typed_cached(self, query, types).await
+    }
+    async fn query_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        Transaction::query_cached(self, query, params).await
+    }
+    async fn query_one_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error> {
+        Transaction::query_one_cached(self, query, params).await
+    }
+    async fn query_opt_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error> {
+        Transaction::query_opt_cached(self, query, params).await
+    }
+    async fn execute_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        Transaction::execute_cached(self, query, params).await
+    }
+    async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        Transaction::batch_execute(self, query).await
+    }
+    async fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+        Transaction::transaction(self).await
+    }
+}
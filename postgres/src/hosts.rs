@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio_postgres::config::Host;
+use tokio_postgres::Config as PgConfig;
+
+/// Hosts which failed to connect are skipped for this duration
+const EXCLUSION_PERIOD: Duration = Duration::from_secs(30);
+
+struct HostState {
+    config: PgConfig,
+    /// Time of the last failed connection attempt
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl HostState {
+    fn is_excluded(&self) -> bool {
+        match *self.failed_at.lock().unwrap() {
+            Some(failed_at) => failed_at.elapsed() < EXCLUSION_PERIOD,
+            None => false,
+        }
+    }
+}
+
+/// Distributes new connections across the hosts of a `PgConfig` in a
+/// round-robin fashion.
+pub(crate) struct HostRotation {
+    hosts: Vec<HostState>,
+    next: AtomicUsize,
+}
+
+impl HostRotation {
+    /// Split the given `PgConfig` into one config per host
+    pub(crate) fn new(config: &PgConfig) -> Self {
+        HostRotation {
+            hosts: (0..config.get_hosts().len())
+                .map(|index| HostState {
+                    config: single_host_config(config, index),
+                    failed_at: Mutex::new(None),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+    /// The order in which the hosts should be tried for the next
+    /// connection. Hosts which recently failed are moved to the end so
+    /// they are only used if all other hosts fail, too.
+    pub(crate) fn order(&self) -> Vec<usize> {
+        let len = self.hosts.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let (mut order, excluded): (Vec<usize>, Vec<usize>) = (0..len)
+            .map(|i| (start + i) % len)
+            .partition(|&index| !self.hosts[index].is_excluded());
+        order.extend(excluded);
+        order
+    }
+    pub(crate) fn config(&self, index: usize) -> &PgConfig {
+        &self.hosts[index].config
+    }
+    pub(crate) fn mark_failed(&self, index: usize) {
+        *self.hosts[index].failed_at.lock().unwrap() = Some(Instant::now());
+    }
+    pub(crate) fn mark_healthy(&self, index: usize) {
+        *self.hosts[index].failed_at.lock().unwrap() = None;
+    }
+}
+
+/// Create a copy of `config` which only contains the host at `index`
+fn single_host_config(config: &PgConfig, index: usize) -> PgConfig {
+    let mut cfg = without_hosts(config);
+    match &config.get_hosts()[index] {
+        Host::Tcp(host) => cfg.host(host),
+        #[cfg(unix)]
+        Host::Unix(path) => cfg.host_path(path),
+    };
+    // A single port applies to all hosts
+    let ports = config.get_ports();
+    if let Some(port) = ports.get(index).or_else(|| ports.first()) {
+        cfg.port(*port);
+    }
+    cfg
+}
+
+/// Create a copy of `config` without any hosts and ports
+pub(crate) fn without_hosts(config: &PgConfig) -> PgConfig {
+    let mut cfg = PgConfig::new();
+    if let Some(user) = config.get_user() {
+        cfg.user(user);
+    }
+    if let Some(password) = config.get_password() {
+        cfg.password(password);
+    }
+    if let Some(dbname) = config.get_dbname() {
+        cfg.dbname(dbname);
+    }
+    if let Some(options) = config.get_options() {
+        cfg.options(options);
+    }
+    if let Some(application_name) = config.get_application_name() {
+        cfg.application_name(application_name);
+    }
+    if let Some(connect_timeout) = config.get_connect_timeout() {
+        cfg.connect_timeout(*connect_timeout);
+    }
+    cfg.ssl_mode(config.get_ssl_mode());
+    cfg.keepalives(config.get_keepalives());
+    cfg.keepalives_idle(config.get_keepalives_idle());
+    cfg.target_session_attrs(config.get_target_session_attrs());
+    cfg
+}
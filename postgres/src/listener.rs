@@ -0,0 +1,147 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use deadpool::timer::{Timer, TokioTimer};
+use futures::channel::{mpsc, oneshot};
+use futures::{future, pin_mut, select, stream, FutureExt, Stream, TryStreamExt};
+use log::warn;
+use tokio_postgres::{
+    tls::MakeTlsConnect, tls::TlsConnect, AsyncMessage, Config as PgConfig, Error, Notification,
+    Socket,
+};
+
+use crate::{quote_ident, Manager, TlsHandle};
+
+/// Delay between reconnection attempts
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// A stream of notifications received via `LISTEN`.
+///
+/// The listener owns a dedicated connection which is not part of any pool.
+/// If the connection is lost the listener reconnects and executes `LISTEN`
+/// for all channels again. Notifications sent while the listener was
+/// disconnected are lost.
+///
+/// The connection is closed when the listener is dropped.
+///
+/// Received notifications are buffered until they are consumed, so a slow
+/// consumer does not cause notifications to be lost.
+///
+/// ```rust,ignore
+/// use futures::StreamExt;
+///
+/// let mut listener = mgr.dedicated_listener(&["jobs"]);
+/// while let Some(notification) = listener.next().await {
+///     println!("{}: {}", notification.channel(), notification.payload());
+/// }
+/// ```
+pub struct Listener {
+    receiver: mpsc::UnboundedReceiver<Notification>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl Stream for Listener {
+    type Item = Notification;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Notification>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl<T, W> Manager<T, W>
+where
+    T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+    T::Stream: Sync + Send,
+    T::TlsConnect: Sync + Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Create a `Listener` which listens for notifications on the given
+    /// channels using a dedicated connection. The channel names are quoted
+    /// so they are case-sensitive.
+    ///
+    /// Connection errors are logged and the connection is retried until
+    /// the listener is dropped. The background task is spawned on the
+    /// runtime set via `Manager::set_runtime` if any.
+    pub fn dedicated_listener(&self, channels: &[&str]) -> Listener {
+        let (sender, receiver) = mpsc::unbounded();
+        let (shutdown_sender, shutdown) = oneshot::channel();
+        let listen_sql: Vec<String> = channels
+            .iter()
+            .map(|channel| format!("LISTEN {}", quote_ident(channel)))
+            .collect();
+        self.spawn(run(
+            self.config.clone(),
+            self.tls.clone(),
+            listen_sql.join(";"),
+            sender,
+            shutdown,
+        ));
+        Listener {
+            receiver,
+            _shutdown: shutdown_sender,
+        }
+    }
+}
+
+async fn run<T>(
+    config: PgConfig,
+    tls: TlsHandle<T>,
+    listen_sql: String,
+    sender: mpsc::UnboundedSender<Notification>,
+    mut shutdown: oneshot::Receiver<()>,
+) where
+    T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+    T::Stream: Sync + Send,
+    T::TlsConnect: Sync + Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    loop {
+        match session(&config, tls.get(), &listen_sql, &sender, &mut shutdown).await {
+            Ok(true) => return,
+            Ok(false) => warn!(target: "deadpool.postgres", "Listener connection closed"),
+            Err(e) => warn!(target: "deadpool.postgres", "Listener connection error: {}", e),
+        }
+        TokioTimer.sleep(RECONNECT_DELAY).await;
+        if sender.is_closed() || shutdown.try_recv().is_err() {
+            return;
+        }
+    }
+}
+
+/// Connect, execute `LISTEN` and forward notifications until the
+/// connection is closed. Returns `true` if the listener was dropped.
+async fn session<T>(
+    config: &PgConfig,
+    tls: T,
+    listen_sql: &str,
+    sender: &mpsc::UnboundedSender<Notification>,
+    shutdown: &mut oneshot::Receiver<()>,
+) -> Result<bool, Error>
+where
+    T: MakeTlsConnect<Socket>,
+{
+    let (client, mut connection) = config.connect(tls).await?;
+    let sender = sender.clone();
+    let forward = stream::poll_fn(move |cx| connection.poll_message(cx))
+        .try_for_each(move |message| {
+            if let AsyncMessage::Notification(notification) = message {
+                // The receiver is only dropped together with the shutdown
+                // sender which ends this session anyways.
+                let _ = sender.unbounded_send(notification);
+            }
+            future::ready(Ok(()))
+        })
+        .fuse();
+    let listen = async {
+        client.batch_execute(listen_sql).await?;
+        // Keep the client alive as the connection is closed otherwise
+        future::pending::<Result<(), Error>>().await
+    }
+    .fuse();
+    pin_mut!(forward, listen);
+    select! {
+        result = forward => result.map(|_| false),
+        result = listen => result.map(|_| false),
+        _ = shutdown => Ok(true),
+    }
+}
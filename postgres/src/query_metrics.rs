@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::warn;
+
+/// Number of recent execution times kept per query for calculating the
+/// percentiles
+const SAMPLES: usize = 1024;
+
+/// Maximum number of distinct queries for which execution times are kept.
+/// Queries are keyed by their SQL text, so applications which build SQL
+/// dynamically would otherwise grow the metrics without bound.
+const MAX_QUERIES: usize = 1024;
+
+/// A snapshot of the execution times of a single query
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryStats {
+    /// Number of executions
+    pub count: u64,
+    /// Total execution time of all executions
+    pub total: Duration,
+    /// 95th percentile of the execution time. This is calculated from the
+    /// last 1024 executions.
+    pub p95: Duration,
+}
+
+struct QueryTimes {
+    /// Value of `QueryMap::clock` when the query was last recorded
+    last_used: u64,
+    count: u64,
+    total: Duration,
+    /// Ring buffer of the most recent execution times
+    samples: Vec<Duration>,
+    next: usize,
+}
+
+impl QueryTimes {
+    fn new() -> Self {
+        QueryTimes {
+            last_used: 0,
+            count: 0,
+            total: Duration::default(),
+            samples: Vec::new(),
+            next: 0,
+        }
+    }
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        if self.samples.len() < SAMPLES {
+            self.samples.push(elapsed);
+        } else {
+            self.samples[self.next] = elapsed;
+            self.next = (self.next + 1) % SAMPLES;
+        }
+    }
+    fn stats(&self) -> QueryStats {
+        let mut samples = self.samples.clone();
+        samples.sort();
+        let p95 = match samples.len() {
+            0 => Duration::default(),
+            len => samples[(len * 95 + 99) / 100 - 1],
+        };
+        QueryStats {
+            count: self.count,
+            total: self.total,
+            p95,
+        }
+    }
+}
+
+/// Execution times of the queries run via the `*_cached` methods of
+/// `Client` and `Transaction` aggregated over all clients of a pool.
+///
+/// This is a cheap handle which can be cloned and kept around after the
+/// manager was moved into the pool. See `Manager::query_metrics`. Times are
+/// only recorded if `ManagerConfig::query_metrics` is enabled.
+///
+/// At most 1024 distinct queries are tracked. When a new query is recorded
+/// and the limit is reached the least recently executed query is removed.
+#[derive(Clone, Default)]
+pub struct QueryMetrics {
+    inner: Arc<Mutex<QueryMap>>,
+}
+
+#[derive(Default)]
+struct QueryMap {
+    map: HashMap<String, QueryTimes>,
+    clock: u64,
+}
+
+impl QueryMetrics {
+    /// Retrieve a snapshot of the execution times of all queries
+    pub fn stats(&self) -> HashMap<String, QueryStats> {
+        self.inner
+            .lock()
+            .unwrap()
+            .map
+            .iter()
+            .map(|(query, times)| (query.clone(), times.stats()))
+            .collect()
+    }
+    /// Retrieve a snapshot of the execution times of a single query
+    pub fn get(&self, query: &str) -> Option<QueryStats> {
+        self.inner
+            .lock()
+            .unwrap()
+            .map
+            .get(query)
+            .map(QueryTimes::stats)
+    }
+    /// Remove all recorded execution times
+    pub fn reset(&self) {
+        self.inner.lock().unwrap().map.clear();
+    }
+    pub(crate) fn record(&self, query: &str, elapsed: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+        if let Some(times) = inner.map.get_mut(query) {
+            times.last_used = clock;
+            times.record(elapsed);
+            return;
+        }
+        if inner.map.len() >= MAX_QUERIES {
+            let oldest = inner
+                .map
+                .iter()
+                .min_by_key(|(_, times)| times.last_used)
+                .map(|(query, _)| query.clone())
+                .unwrap();
+            inner.map.remove(&oldest);
+        }
+        let mut times = QueryTimes::new();
+        times.last_used = clock;
+        times.record(elapsed);
+        inner.map.insert(query.to_owned(), times);
+    }
+}
+
+/// A query which took longer than `ManagerConfig::slow_query_threshold`
+#[derive(Clone, Copy, Debug)]
+pub struct SlowQuery<'a> {
+    /// The SQL text of the query
+    pub query: &'a str,
+    /// Number of parameters passed to the query
+    pub params: usize,
+    /// Execution time of the query
+    pub elapsed: Duration,
+}
+
+/// Callback which is called for every slow query. See
+/// `Manager::set_slow_query_callback`.
+pub type SlowQueryCallback = Arc<dyn Fn(&SlowQuery) + Send + Sync>;
+
+#[derive(Clone)]
+pub(crate) struct SlowQueryLog {
+    pub(crate) threshold: Duration,
+    /// Slow queries are logged if no callback is set
+    pub(crate) callback: Option<SlowQueryCallback>,
+}
+
+impl SlowQueryLog {
+    pub(crate) fn check(&self, slow_query: &SlowQuery) {
+        if slow_query.elapsed < self.threshold {
+            return;
+        }
+        match &self.callback {
+            Some(callback) => callback(slow_query),
+            None => warn!(
+                target: "deadpool.postgres",
+                "Slow query ({:?}, {} parameters): {}",
+                slow_query.elapsed,
+                slow_query.params,
+                slow_query.query
+            ),
+        }
+    }
+}
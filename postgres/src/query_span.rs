@@ -0,0 +1,108 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tokio_postgres::{Row, RowStream};
+#[cfg(feature = "tracing")]
+use tracing::{field, info_span, Instrument, Span};
+
+/// Maximum number of bytes of the SQL text recorded in a span
+#[cfg(feature = "tracing")]
+const MAX_STATEMENT_LEN: usize = 1024;
+
+/// Measures the execution time of a query run via one of the `*_cached`
+/// methods. With the `tracing` feature the query is also recorded as a
+/// `postgres.query` span including the truncated SQL text, the number of
+/// rows and the duration.
+pub(crate) struct QueryTimer {
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    span: Span,
+}
+
+impl QueryTimer {
+    pub(crate) fn start(#[allow(unused_variables)] query: &str) -> Self {
+        QueryTimer {
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span: info_span!(
+                "postgres.query",
+                db.system = "postgresql",
+                db.statement = truncate(query),
+                db.rows = field::Empty,
+                db.duration_ms = field::Empty,
+            ),
+        }
+    }
+    /// Run the given query future inside the span, so events logged while
+    /// executing the query are attached to it.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        future.instrument(self.span.clone())
+    }
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        future
+    }
+    /// Stop the timer and return the elapsed time. `rows` is `None` if the
+    /// query failed.
+    pub(crate) fn finish(self, #[allow(unused_variables)] rows: Option<u64>) -> Duration {
+        let elapsed = self.start.elapsed();
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(rows) = rows {
+                self.span.record("db.rows", &rows);
+            }
+            self.span
+                .record("db.duration_ms", &(elapsed.as_millis() as u64));
+        }
+        elapsed
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn truncate(query: &str) -> &str {
+    if query.len() <= MAX_STATEMENT_LEN {
+        return query;
+    }
+    let mut end = MAX_STATEMENT_LEN;
+    while !query.is_char_boundary(end) {
+        end -= 1;
+    }
+    &query[..end]
+}
+
+/// Number of rows returned or affected by a query. `None` if it is not
+/// known when the query returns, e.g. for a stream of rows.
+pub(crate) trait RowCount {
+    fn row_count(&self) -> Option<u64>;
+}
+
+impl RowCount for Vec<Row> {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+impl RowCount for Row {
+    fn row_count(&self) -> Option<u64> {
+        Some(1)
+    }
+}
+
+impl RowCount for Option<Row> {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.is_some() as u64)
+    }
+}
+
+impl RowCount for u64 {
+    fn row_count(&self) -> Option<u64> {
+        Some(*self)
+    }
+}
+
+impl RowCount for RowStream {
+    fn row_count(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use crate::{Object, Pool, PoolError};
+
+struct Replica {
+    pool: Pool,
+    /// Time of the last failure. Replicas are excluded for the
+    /// `exclusion_period` after a failure.
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl Replica {
+    fn is_excluded(&self, exclusion_period: Duration) -> bool {
+        match *self.failed_at.lock().unwrap() {
+            Some(failed_at) => failed_at.elapsed() < exclusion_period,
+            None => false,
+        }
+    }
+    fn mark_failed(&self) {
+        *self.failed_at.lock().unwrap() = Some(Instant::now());
+    }
+    fn mark_healthy(&self) {
+        *self.failed_at.lock().unwrap() = None;
+    }
+}
+
+/// A pool which manages a primary pool and any number of replica pools.
+///
+/// Use `get` for retrieving a client connected to the primary and
+/// `get_read_only` for retrieving a client connected to one of the
+/// replicas. Replicas are used in a round-robin fashion. If a replica fails
+/// to provide a client it is excluded for the `exclusion_period` and the
+/// next replica is tried. If no replica is available the primary is used.
+pub struct ReplicatedPool {
+    primary: Pool,
+    replicas: Vec<Replica>,
+    next: AtomicUsize,
+    exclusion_period: Duration,
+}
+
+impl ReplicatedPool {
+    /// Create new replicated pool with an `exclusion_period` of 30 seconds
+    pub fn new(primary: Pool, replicas: Vec<Pool>) -> Self {
+        ReplicatedPool {
+            primary,
+            replicas: replicas
+                .into_iter()
+                .map(|pool| Replica {
+                    pool,
+                    failed_at: Mutex::new(None),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            exclusion_period: Duration::from_secs(30),
+        }
+    }
+    /// Set the duration for which a failed replica is excluded
+    pub fn set_exclusion_period(&mut self, exclusion_period: Duration) {
+        self.exclusion_period = exclusion_period;
+    }
+    /// Retrieve a client connected to the primary
+    pub async fn get(&self) -> Result<Object, PoolError> {
+        self.primary.get().await
+    }
+    /// Retrieve a client connected to one of the replicas. If no replica
+    /// is available a client connected to the primary is returned.
+    pub async fn get_read_only(&self) -> Result<Object, PoolError> {
+        let len = self.replicas.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        for i in 0..len {
+            let replica = &self.replicas[(start + i) % len];
+            if replica.is_excluded(self.exclusion_period) {
+                continue;
+            }
+            match replica.pool.get().await {
+                Ok(client) => {
+                    replica.mark_healthy();
+                    return Ok(client);
+                }
+                Err(e) => {
+                    warn!(target: "deadpool.postgres", "Excluding replica: {}", e);
+                    replica.mark_failed();
+                }
+            }
+        }
+        self.primary.get().await
+    }
+    /// Access the primary pool
+    pub fn primary(&self) -> &Pool {
+        &self.primary
+    }
+    /// Access the replica pools
+    pub fn replicas(&self) -> impl Iterator<Item = &Pool> {
+        self.replicas.iter().map(|replica| &replica.pool)
+    }
+}
@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deadpool::timer::{Timer, TokioTimer};
+use futures::future::BoxFuture;
+use tokio_postgres::{error::SqlState, types::ToSql, Error, IsolationLevel, Row};
+
+use crate::{Pool, PoolError, Transaction};
+
+/// Maximum number of attempts. A connection which died while being idle is
+/// only noticed by the first query so every attempt can receive another
+/// dead connection from the pool.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Maximum number of attempts of `PoolRetry::transaction_retry`
+const MAX_TRANSACTION_ATTEMPTS: usize = 10;
+
+/// Delay before the first retry of a transaction. The delay is doubled on
+/// every further retry up to `MAX_TRANSACTION_BACKOFF`.
+const TRANSACTION_BACKOFF: Duration = Duration::from_millis(10);
+
+const MAX_TRANSACTION_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Execute queries on a client of the pool and transparently retry on
+/// another client if the connection turns out to be closed.
+///
+/// Only errors caused by a closed connection are retried. Be aware that a
+/// connection can also be closed while the query is being executed, so
+/// these methods should only be used for idempotent queries.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use deadpool_postgres::PoolRetry;
+///
+/// let rows = pool.query_retry("SELECT 1 + $1", &[&1]).await?;
+/// ```
+#[async_trait]
+pub trait PoolRetry {
+    /// Like `Client::query_cached` but retries on a closed connection
+    async fn query_retry(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, PoolError>;
+    /// Like `Client::query_one_cached` but retries on a closed connection
+    async fn query_one_retry(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, PoolError>;
+    /// Like `Client::execute_cached` but retries on a closed connection
+    async fn execute_retry(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, PoolError>;
+    /// Run the given closure in a `SERIALIZABLE` transaction and commit it.
+    /// If the closure or the commit fails with a serialization failure
+    /// (`40001`) or a deadlock (`40P01`) the transaction is rolled back and
+    /// the closure is called again after a short delay. The delay is
+    /// doubled on every retry and the transaction is attempted at most 10
+    /// times.
+    ///
+    /// The closure must not have side effects outside of the transaction as
+    /// it can be called multiple times.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use deadpool_postgres::PoolRetry;
+    ///
+    /// let balance: i64 = pool
+    ///     .transaction_retry(|txn| {
+    ///         Box::pin(async move {
+    ///             txn.execute_cached("UPDATE account SET balance = balance - 1", &[])
+    ///                 .await?;
+    ///             let row = txn.query_one_cached("SELECT SUM(balance) FROM account", &[])
+    ///                 .await?;
+    ///             Ok(row.get(0))
+    ///         })
+    ///     })
+    ///     .await?;
+    /// ```
+    async fn transaction_retry<F, R>(&self, f: F) -> Result<R, PoolError>
+    where
+        F: for<'t, 'c> FnMut(&'t mut Transaction<'c>) -> BoxFuture<'t, Result<R, Error>> + Send,
+        R: Send;
+}
+
+#[async_trait]
+impl PoolRetry for Pool {
+    async fn query_retry(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, PoolError> {
+        let mut attempt = 1;
+        loop {
+            let mut client = self.get().await?;
+            match client.query_cached(query, params).await {
+                Err(e) if e.is_closed() && attempt < MAX_ATTEMPTS => attempt += 1,
+                result => return Ok(result?),
+            }
+        }
+    }
+    async fn query_one_retry(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, PoolError> {
+        let mut attempt = 1;
+        loop {
+            let mut client = self.get().await?;
+            match client.query_one_cached(query, params).await {
+                Err(e) if e.is_closed() && attempt < MAX_ATTEMPTS => attempt += 1,
+                result => return Ok(result?),
+            }
+        }
+    }
+    async fn execute_retry(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, PoolError> {
+        let mut attempt = 1;
+        loop {
+            let mut client = self.get().await?;
+            match client.execute_cached(query, params).await {
+                Err(e) if e.is_closed() && attempt < MAX_ATTEMPTS => attempt += 1,
+                result => return Ok(result?),
+            }
+        }
+    }
+    async fn transaction_retry<F, R>(&self, mut f: F) -> Result<R, PoolError>
+    where
+        F: for<'t, 'c> FnMut(&'t mut Transaction<'c>) -> BoxFuture<'t, Result<R, Error>> + Send,
+        R: Send,
+    {
+        let mut client = self.get().await?;
+        let mut attempt = 1;
+        let mut backoff = TRANSACTION_BACKOFF;
+        loop {
+            let mut txn = client
+                .build_transaction()
+                .isolation_level(IsolationLevel::Serializable)
+                .start()
+                .await?;
+            // The transaction is rolled back when it is dropped without
+            // being committed.
+            let result = match f(&mut txn).await {
+                Ok(value) => txn.commit().await.map(|_| value),
+                Err(e) => Err(e),
+            };
+            match result {
+                Err(e) if is_serialization_failure(&e) && attempt < MAX_TRANSACTION_ATTEMPTS => {
+                    TokioTimer.sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, MAX_TRANSACTION_BACKOFF);
+                    attempt += 1;
+                }
+                result => return Ok(result?),
+            }
+        }
+    }
+}
+
+fn is_serialization_failure(e: &Error) -> bool {
+    match e.code() {
+        Some(code) => {
+            *code == SqlState::T_R_SERIALIZATION_FAILURE || *code == SqlState::T_R_DEADLOCK_DETECTED
+        }
+        None => false,
+    }
+}
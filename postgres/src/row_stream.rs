@@ -0,0 +1,31 @@
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tokio_postgres::{Error, Row};
+
+/// A stream of rows returned by `query_raw`.
+///
+/// This wraps `tokio_postgres::RowStream` so large result sets can be
+/// consumed incrementally. It borrows the client, see `Client`.
+pub struct RowStream<'a> {
+    stream: Pin<Box<tokio_postgres::RowStream>>,
+    _client: PhantomData<&'a mut ()>,
+}
+
+impl<'a> RowStream<'a> {
+    pub(crate) fn new(stream: tokio_postgres::RowStream) -> Self {
+        RowStream {
+            stream: Box::pin(stream),
+            _client: PhantomData,
+        }
+    }
+}
+
+impl<'a> Stream for RowStream<'a> {
+    type Item = Result<Row, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
@@ -0,0 +1,481 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio_postgres::{types::Type, Error, Statement};
+
+use crate::query_metrics::SlowQueryLog;
+use crate::query_span::{QueryTimer, RowCount};
+use crate::{is_stale_plan, QueryMetrics, SlowQuery};
+
+/// A snapshot of the statement cache counters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatementCacheStats {
+    /// Number of statements which were found in the cache
+    pub hits: u64,
+    /// Number of statements which had to be prepared
+    pub misses: u64,
+    /// Number of statements which were evicted because the cache was full
+    pub evictions: u64,
+    /// Number of statements currently in the cache
+    pub size: usize,
+}
+
+#[derive(Default)]
+struct MetricsInner {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    size: AtomicUsize,
+}
+
+/// Statement cache counters aggregated over all clients of a pool.
+///
+/// This is a cheap handle which can be cloned and kept around after the
+/// manager was moved into the pool. See `Manager::statement_cache_metrics`.
+#[derive(Clone, Default)]
+pub struct StatementCacheMetrics {
+    inner: Arc<MetricsInner>,
+}
+
+impl StatementCacheMetrics {
+    /// Retrieve a snapshot of the aggregated counters
+    pub fn stats(&self) -> StatementCacheStats {
+        StatementCacheStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            evictions: self.inner.evictions.load(Ordering::Relaxed),
+            size: self.inner.size.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Default of `StatementCacheConfig::statement_memory`
+const DEFAULT_STATEMENT_MEMORY: usize = 4096;
+
+/// Configuration of the statement cache of every client created by the
+/// `Manager`. When read from a configuration file or environment variables
+/// the `ttl` is given in milliseconds.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct StatementCacheConfig {
+    /// Maximum number of statements cached per client. `None` means
+    /// unbounded. See `StatementCache::set_max_size`.
+    pub max_size: Option<usize>,
+    /// Statements which were prepared longer ago than this are prepared
+    /// again on their next use. See `StatementCache::set_ttl`.
+    #[cfg_attr(
+        feature = "config",
+        serde(deserialize_with = "deadpool::serde_duration::millis")
+    )]
+    pub ttl: Option<Duration>,
+    /// Do not cache any statements. The `*_cached` methods prepare the
+    /// statement on every call.
+    pub disabled: bool,
+    /// Upper limit of the estimated server-side memory used by the cached
+    /// statements of a single connection in bytes. `None` means unbounded.
+    /// See `StatementCache::set_max_memory`.
+    pub max_memory: Option<usize>,
+    /// Estimated server-side memory of a single prepared statement in
+    /// bytes not counting the SQL text. Defaults to 4096.
+    pub statement_memory: usize,
+}
+
+impl Default for StatementCacheConfig {
+    fn default() -> Self {
+        StatementCacheConfig {
+            max_size: None,
+            ttl: None,
+            disabled: false,
+            max_memory: None,
+            statement_memory: DEFAULT_STATEMENT_MEMORY,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct StatementCacheKey {
+    query: String,
+    types: Vec<Type>,
+}
+
+impl StatementCacheKey {
+    fn new(query: &str, types: &[Type]) -> Self {
+        StatementCacheKey {
+            query: query.to_owned(),
+            types: types.to_owned(),
+        }
+    }
+}
+
+struct CacheEntry {
+    statement: Statement,
+    /// Value of `StatementCache::clock` when this entry was used the
+    /// last time.
+    last_used: u64,
+    prepared_at: Instant,
+    last_used_at: Instant,
+    hits: u64,
+    /// Estimated server-side memory of the statement
+    memory: usize,
+}
+
+/// Usage statistics of a single cached statement. See
+/// `StatementCache::usage`.
+#[derive(Clone, Copy, Debug)]
+pub struct StatementUsage<'a> {
+    /// The SQL text of the statement
+    pub query: &'a str,
+    /// The explicitly given parameter types
+    pub types: &'a [Type],
+    /// Number of times the statement was found in the cache
+    pub hits: u64,
+    /// Time when the statement was prepared
+    pub prepared_at: Instant,
+    /// Time when the statement was used the last time
+    pub last_used: Instant,
+}
+
+/// This structure holds the cached statements and provides access to
+/// functions for retrieving the current size and clearing the cache.
+///
+/// Statements are cached by their query and the explicitly given parameter
+/// types. Preparing the same query with different types results in
+/// separate cache entries.
+///
+/// The cache can be bounded by setting a `max_size`. When the cache is full
+/// the least recently used statement is evicted. Evicted statements are
+/// closed on the server once the last reference to them is dropped.
+///
+/// Alternatively the cache can be bounded by the memory the statements use
+/// on the server via `max_memory`. This is a rough estimate which consists
+/// of a fixed amount per statement (`StatementCacheConfig::statement_memory`)
+/// and the length of its SQL text. The actual memory used by a prepared
+/// statement depends on the complexity of its plan.
+///
+/// Custom statement names (e.g. a per-pool prefix) and unnamed statements
+/// are not supported. `tokio_postgres` names every prepared statement
+/// itself (`s0`, `s1`, ...) and provides no way to change this. Use
+/// `ManagerConfig::application_name` instead to attribute connections and
+/// their statements to a service. It is visible in `pg_stat_activity` and
+/// can be included in the server logs via `log_line_prefix`.
+pub struct StatementCache {
+    map: HashMap<StatementCacheKey, CacheEntry>,
+    /// The keys of `map` ordered by `CacheEntry::last_used`
+    lru: BTreeMap<u64, StatementCacheKey>,
+    /// Sum of `CacheEntry::memory` of all entries
+    memory: usize,
+    max_size: Option<usize>,
+    ttl: Option<Duration>,
+    disabled: bool,
+    max_memory: Option<usize>,
+    statement_memory: usize,
+    clock: u64,
+    stats: StatementCacheStats,
+    metrics: StatementCacheMetrics,
+    /// Set if `ManagerConfig::query_metrics` is enabled
+    pub(crate) query_metrics: Option<QueryMetrics>,
+    /// Set if `ManagerConfig::slow_query_threshold` is configured
+    pub(crate) slow_query_log: Option<SlowQueryLog>,
+}
+
+impl StatementCache {
+    pub(crate) fn new(
+        config: &StatementCacheConfig,
+        metrics: StatementCacheMetrics,
+    ) -> StatementCache {
+        StatementCache {
+            map: HashMap::new(),
+            lru: BTreeMap::new(),
+            memory: 0,
+            max_size: config.max_size,
+            ttl: config.ttl,
+            disabled: config.disabled,
+            max_memory: config.max_memory,
+            statement_memory: config.statement_memory,
+            clock: 0,
+            stats: StatementCacheStats::default(),
+            metrics,
+            query_metrics: None,
+            slow_query_log: None,
+        }
+    }
+    /// Retrieve current size of the cache
+    pub fn size(&self) -> usize {
+        self.map.len()
+    }
+    /// Retrieve the counters of this cache
+    pub fn stats(&self) -> StatementCacheStats {
+        StatementCacheStats {
+            size: self.map.len(),
+            ..self.stats
+        }
+    }
+    /// Retrieve the maximum size of the cache. `None` means unbounded.
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+    /// Set the maximum size of the cache. If the cache currently contains
+    /// more statements the least recently used ones are evicted.
+    pub fn set_max_size(&mut self, max_size: Option<usize>) {
+        self.max_size = max_size;
+        self.evict(0, 0);
+    }
+    /// Retrieve the estimated server-side memory used by the cached
+    /// statements in bytes
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+    /// Retrieve the upper limit of the estimated server-side memory in
+    /// bytes. `None` means unbounded.
+    pub fn max_memory(&self) -> Option<usize> {
+        self.max_memory
+    }
+    /// Set the upper limit of the estimated server-side memory in bytes. If
+    /// the cache currently uses more memory the least recently used
+    /// statements are evicted.
+    pub fn set_max_memory(&mut self, max_memory: Option<usize>) {
+        self.max_memory = max_memory;
+        self.evict(0, 0);
+    }
+    /// Retrieve the time after which cached statements are prepared again.
+    /// `None` means statements never expire.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+    /// Set the time after which cached statements are prepared again.
+    /// Expired statements are removed from the cache on their next use.
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+    }
+    /// Returns `true` if caching is disabled
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+    /// Enable or disable caching. Disabling the cache also clears it.
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+        if disabled {
+            self.clear();
+        }
+    }
+    /// Clear cache
+    ///
+    /// `tokio_postgres` closes a prepared statement on the server when the
+    /// last reference to it is dropped. The removed statements are
+    /// therefore closed right away unless they are still referenced
+    /// elsewhere, e.g. by a `Statement` kept by the application.
+    pub fn clear(&mut self) {
+        self.metrics
+            .inner
+            .size
+            .fetch_sub(self.map.len(), Ordering::Relaxed);
+        self.map.clear();
+        self.lru.clear();
+        self.memory = 0;
+    }
+    /// Remove a single statement from the cache. This is useful for
+    /// invalidating a statement after a schema migration without clearing
+    /// the entire cache. The statement is closed on the server once the
+    /// returned value and all other references to it are dropped.
+    ///
+    /// Statements are cached by query and parameter types. Use an empty
+    /// slice for `types` to remove a statement created by `prepare`.
+    pub fn remove(&mut self, query: &str, types: &[Type]) -> Option<Statement> {
+        let entry = self.remove_entry(&StatementCacheKey::new(query, types))?;
+        Some(entry.statement)
+    }
+    /// Retrieve the usage statistics of all cached statements. This is
+    /// useful for finding the statements which dominate the workload and
+    /// the ones which are never used again.
+    pub fn usage(&self) -> impl Iterator<Item = StatementUsage<'_>> + '_ {
+        self.map.iter().map(|(key, entry)| StatementUsage {
+            query: &key.query,
+            types: &key.types,
+            hits: entry.hits,
+            prepared_at: entry.prepared_at,
+            last_used: entry.last_used_at,
+        })
+    }
+    pub(crate) fn record_query(&self, query: &str, params: usize, elapsed: Duration) {
+        if let Some(query_metrics) = &self.query_metrics {
+            query_metrics.record(query, elapsed);
+        }
+        if let Some(slow_query_log) = &self.slow_query_log {
+            slow_query_log.check(&SlowQuery {
+                query,
+                params,
+                elapsed,
+            });
+        }
+    }
+    /// Return the cached statement for `query` and `types` or create it
+    /// using `prepare` and insert it into the cache.
+    pub(crate) async fn prepare_with<P, F>(
+        &mut self,
+        query: &str,
+        types: &[Type],
+        prepare: P,
+    ) -> Result<Statement, Error>
+    where
+        P: FnOnce() -> F,
+        F: Future<Output = Result<Statement, Error>>,
+    {
+        match self.get(query, types) {
+            Some(statement) => Ok(statement),
+            None => {
+                let statement = prepare().await?;
+                self.insert(query, types, &statement);
+                Ok(statement)
+            }
+        }
+    }
+    /// Run a query using a cached statement. This is used by all `*_cached`
+    /// methods: the execution is timed and recorded and a statement which
+    /// fails with "cached plan must not change result type" is removed from
+    /// the cache. If `retry` is set the statement is then prepared again and
+    /// the query is executed a second time.
+    pub(crate) async fn run_cached<T, P, PF, E, EF>(
+        &mut self,
+        query: &str,
+        types: &[Type],
+        params: usize,
+        retry: bool,
+        prepare: P,
+        execute: E,
+    ) -> Result<T, Error>
+    where
+        T: RowCount,
+        P: Fn() -> PF,
+        PF: Future<Output = Result<Statement, Error>>,
+        E: Fn(Statement) -> EF,
+        EF: Future<Output = Result<T, Error>>,
+    {
+        let statement = self.prepare_with(query, types, &prepare).await?;
+        let timer = QueryTimer::start(query);
+        let result = match timer.instrument(execute(statement)).await {
+            Err(e) if is_stale_plan(&e) => {
+                self.remove(query, types);
+                if retry {
+                    let statement = self.prepare_with(query, types, &prepare).await?;
+                    timer.instrument(execute(statement)).await
+                } else {
+                    Err(e)
+                }
+            }
+            result => result,
+        };
+        let elapsed = timer.finish(result.as_ref().ok().and_then(RowCount::row_count));
+        self.record_query(query, params, elapsed);
+        result
+    }
+    pub(crate) fn get(&mut self, query: &str, types: &[Type]) -> Option<Statement> {
+        self.clock += 1;
+        let clock = self.clock;
+        let key = StatementCacheKey::new(query, types);
+        if let Some(ttl) = self.ttl {
+            let expired = match self.map.get(&key) {
+                Some(entry) => entry.prepared_at.elapsed() >= ttl,
+                None => false,
+            };
+            if expired {
+                self.remove_entry(&key);
+            }
+        }
+        let lru = &mut self.lru;
+        let statement = self.map.get_mut(&key).map(|entry| {
+            lru.remove(&entry.last_used);
+            entry.last_used = clock;
+            entry.last_used_at = Instant::now();
+            entry.hits += 1;
+            entry.statement.clone()
+        });
+        if statement.is_some() {
+            self.lru.insert(clock, key);
+        }
+        if statement.is_some() {
+            self.stats.hits += 1;
+            self.metrics.inner.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.stats.misses += 1;
+            self.metrics.inner.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        statement
+    }
+    pub(crate) fn insert(&mut self, query: &str, types: &[Type], statement: &Statement) {
+        if self.disabled || self.max_size == Some(0) {
+            return;
+        }
+        let memory = self.statement_memory + query.len();
+        if self
+            .max_memory
+            .map_or(false, |max_memory| memory > max_memory)
+        {
+            return;
+        }
+        let key = StatementCacheKey::new(query, types);
+        // Replacing a cached statement does not need any room
+        if self.remove_entry(&key).is_none() {
+            self.evict(1, memory);
+        }
+        self.clock += 1;
+        let now = Instant::now();
+        self.lru.insert(self.clock, key.clone());
+        self.memory += memory;
+        self.map.insert(
+            key,
+            CacheEntry {
+                statement: statement.clone(),
+                last_used: self.clock,
+                prepared_at: now,
+                last_used_at: now,
+                hits: 0,
+                memory,
+            },
+        );
+        self.metrics.inner.size.fetch_add(1, Ordering::Relaxed);
+    }
+    fn remove_entry(&mut self, key: &StatementCacheKey) -> Option<CacheEntry> {
+        let entry = self.map.remove(key)?;
+        self.lru.remove(&entry.last_used);
+        self.memory -= entry.memory;
+        self.metrics.inner.size.fetch_sub(1, Ordering::Relaxed);
+        Some(entry)
+    }
+    /// Evict least recently used statements until there is room for
+    /// `additional` statements using `additional_memory` bytes.
+    fn evict(&mut self, additional: usize, additional_memory: usize) {
+        if self.max_size.is_none() && self.max_memory.is_none() {
+            return;
+        }
+        while !self.map.is_empty() {
+            let too_many = match self.max_size {
+                Some(max_size) => self.map.len() + additional > max_size,
+                None => false,
+            };
+            let too_large = match self.max_memory {
+                Some(max_memory) => self.memory + additional_memory > max_memory,
+                None => false,
+            };
+            if !too_many && !too_large {
+                break;
+            }
+            let oldest = self.lru.values().next().unwrap().clone();
+            self.remove_entry(&oldest);
+            self.stats.evictions += 1;
+            self.metrics.inner.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for StatementCache {
+    fn drop(&mut self) {
+        self.metrics
+            .inner
+            .size
+            .fetch_sub(self.map.len(), Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,130 @@
+use deadpool::KeyedPool;
+use tokio_postgres::{tls::MakeTlsConnect, tls::TlsConnect, Config as PgConfig, Socket};
+
+use crate::{Client, Manager, ManagerConfig, Object, Pool, PoolError};
+
+/// This enum is used to control how tenants are separated by `TenantPools`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TenantIsolation {
+    /// Every tenant uses a schema named like the tenant. The schema is put
+    /// in front of the `search_path` of the `ManagerConfig`.
+    Schema,
+    /// Every tenant uses a database named like the tenant.
+    Database,
+}
+
+/// A set of pools with one pool per tenant which are created on demand
+/// from a shared base configuration.
+///
+/// Pools of tenants which are not in use can be evicted in order to stay
+/// within a global connection budget. See `deadpool::KeyedPool`.
+pub struct TenantPools {
+    pools: KeyedPool<String, Client, PoolError>,
+}
+
+impl TenantPools {
+    /// Create tenant pools without a global limit. Every tenant pool is
+    /// limited to `tenant_max_size` connections.
+    pub fn new<T>(
+        config: PgConfig,
+        tls: T,
+        manager_config: ManagerConfig,
+        isolation: TenantIsolation,
+        tenant_max_size: usize,
+    ) -> Self
+    where
+        T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+        T::Stream: Sync + Send,
+        T::TlsConnect: Sync + Send,
+        <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+    {
+        TenantPools {
+            pools: KeyedPool::new(factory(
+                config,
+                tls,
+                manager_config,
+                isolation,
+                tenant_max_size,
+            )),
+        }
+    }
+    /// Create tenant pools which are limited to `max_size` connections in
+    /// total. Every tenant pool is limited to `tenant_max_size` connections.
+    ///
+    /// See `deadpool::KeyedPool::with_max_size` for how the global limit is
+    /// enforced.
+    pub fn with_max_size<T>(
+        config: PgConfig,
+        tls: T,
+        manager_config: ManagerConfig,
+        isolation: TenantIsolation,
+        tenant_max_size: usize,
+        max_size: usize,
+    ) -> Self
+    where
+        T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+        T::Stream: Sync + Send,
+        T::TlsConnect: Sync + Send,
+        <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+    {
+        TenantPools {
+            pools: KeyedPool::with_max_size(
+                factory(config, tls, manager_config, isolation, tenant_max_size),
+                max_size,
+            ),
+        }
+    }
+    /// Retrieve a client of the given tenant
+    pub async fn get(&self, tenant: &str) -> Result<Object, PoolError> {
+        self.pools.get(&tenant.to_owned()).await
+    }
+    /// Remove the pools of all tenants which are not in use and have not
+    /// been used since the last call to this method. Returns the number of
+    /// removed pools.
+    pub fn evict_idle(&self) -> usize {
+        self.pools.evict_idle()
+    }
+    /// Remove the pool of the given tenant
+    pub fn remove(&self, tenant: &str) -> Option<Pool> {
+        self.pools.remove(&tenant.to_owned())
+    }
+    /// Number of tenants which currently have a pool
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+    /// Returns `true` if no tenant has a pool
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+}
+
+fn factory<T>(
+    config: PgConfig,
+    tls: T,
+    manager_config: ManagerConfig,
+    isolation: TenantIsolation,
+    tenant_max_size: usize,
+) -> impl Fn(&String) -> Pool + Send + Sync + 'static
+where
+    T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+    T::Stream: Sync + Send,
+    T::TlsConnect: Sync + Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    move |tenant| {
+        let mut config = config.clone();
+        let mut manager_config = manager_config.clone();
+        match isolation {
+            TenantIsolation::Schema => {
+                let mut search_path = vec![tenant.clone()];
+                search_path.extend(manager_config.search_path.take().unwrap_or_default());
+                manager_config.search_path = Some(search_path);
+            }
+            TenantIsolation::Database => {
+                config.dbname(tenant);
+            }
+        }
+        let manager = Manager::from_config(config, tls.clone(), manager_config);
+        Pool::new(manager, tenant_max_size)
+    }
+}
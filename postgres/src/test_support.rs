@@ -0,0 +1,60 @@
+use testcontainers::{clients::Cli, images::postgres::Postgres, Container, Docker};
+use tokio_postgres::{Config as PgConfig, NoTls};
+
+use crate::{Manager, ManagerConfig, Pool};
+
+/// A throwaway PostgreSQL server running in a docker container which is
+/// used for integration tests. The container is removed when this object
+/// is dropped.
+///
+/// This requires the `test-support` feature and a running docker daemon.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use deadpool_postgres::test_support::TestDatabase;
+/// use testcontainers::clients::Cli;
+///
+/// let docker = Cli::default();
+/// let db = TestDatabase::start(&docker);
+/// let pool = db.create_pool(Default::default(), 4);
+/// let client = pool.get().await.unwrap();
+/// ```
+pub struct TestDatabase<'d> {
+    container: Container<'d, Cli, Postgres>,
+    config: PgConfig,
+}
+
+impl<'d> TestDatabase<'d> {
+    /// Start a new PostgreSQL container and wait until it accepts
+    /// connections
+    pub fn start(docker: &'d Cli) -> Self {
+        let container = docker.run(Postgres::default());
+        let port = container
+            .get_host_port(5432)
+            .expect("PostgreSQL port is not exposed");
+        let mut config = PgConfig::new();
+        config.host("127.0.0.1");
+        config.port(port);
+        config.user("postgres");
+        config.dbname("postgres");
+        TestDatabase { container, config }
+    }
+    /// Retrieve the configuration for connecting to the database
+    pub fn pg_config(&self) -> PgConfig {
+        self.config.clone()
+    }
+    /// Create a manager connecting to the database
+    pub fn create_manager(&self, manager_config: ManagerConfig) -> Manager<NoTls> {
+        Manager::from_config(self.pg_config(), NoTls, manager_config)
+    }
+    /// Create a pool connecting to the database
+    pub fn create_pool(&self, manager_config: ManagerConfig, max_size: usize) -> Pool {
+        Pool::new(self.create_manager(manager_config), max_size)
+    }
+    /// Access the container, e.g. for stopping and starting it in order to
+    /// simulate a failover
+    pub fn container(&self) -> &Container<'d, Cli, Postgres> {
+        &self.container
+    }
+}
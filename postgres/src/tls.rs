@@ -0,0 +1,167 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use tokio_postgres::Config as PgConfig;
+
+use crate::Manager;
+
+/// Configuration of the built-in TLS connectors which are available via
+/// the `native-tls` and `rustls` features.
+///
+/// All certificates and keys are read from files when the connector is
+/// created.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct TlsConfig {
+    /// PEM encoded root certificates which are trusted in addition to the
+    /// root certificates of the operating system
+    pub root_certs: Vec<PathBuf>,
+    /// PEM encoded client certificate chain (`rustls` only)
+    pub client_cert: Option<PathBuf>,
+    /// PEM encoded PKCS#8 or RSA private key of the client certificate
+    /// (`rustls` only)
+    pub client_key: Option<PathBuf>,
+    /// DER encoded PKCS#12 archive containing the client certificate and
+    /// private key (`native-tls` only)
+    pub client_identity: Option<PathBuf>,
+    /// Password of the `client_identity` archive (`native-tls` only)
+    pub client_identity_password: Option<String>,
+}
+
+/// This error is returned if a TLS connector could not be created from a
+/// `TlsConfig`.
+#[derive(Debug)]
+pub enum TlsError {
+    /// A certificate or key file could not be read
+    Io(PathBuf, io::Error),
+    /// A certificate or key file does not contain a valid certificate or key
+    Invalid(PathBuf),
+    /// The `client_cert` was configured without `client_key` or vice versa
+    IncompleteClientCert,
+    /// Error of the `native-tls` crate
+    #[cfg(feature = "native-tls")]
+    NativeTls(native_tls_crate::Error),
+    /// The root certificates of the operating system could not be loaded
+    #[cfg(feature = "rustls")]
+    NativeCerts(io::Error),
+    /// Error of the `rustls` crate
+    #[cfg(feature = "rustls")]
+    Rustls(rustls_crate::TLSError),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsError::Io(path, e) => write!(f, "Could not read {}: {}", path.display(), e),
+            TlsError::Invalid(path) => write!(f, "Invalid certificate or key: {}", path.display()),
+            TlsError::IncompleteClientCert => {
+                write!(f, "client_cert and client_key must be configured together")
+            }
+            #[cfg(feature = "native-tls")]
+            TlsError::NativeTls(e) => write!(f, "TLS error: {}", e),
+            #[cfg(feature = "rustls")]
+            TlsError::NativeCerts(e) => write!(f, "Could not load root certificates: {}", e),
+            #[cfg(feature = "rustls")]
+            TlsError::Rustls(e) => write!(f, "TLS error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+fn read_file(path: &PathBuf) -> Result<Vec<u8>, TlsError> {
+    fs::read(path).map_err(|e| TlsError::Io(path.clone(), e))
+}
+
+#[cfg(feature = "native-tls")]
+impl TlsConfig {
+    /// Create a `MakeTlsConnect` implementation using `native-tls`
+    pub fn make_native_tls_connector(
+        &self,
+    ) -> Result<postgres_native_tls::MakeTlsConnector, TlsError> {
+        use native_tls_crate::{Certificate, Identity, TlsConnector};
+        let mut builder = TlsConnector::builder();
+        for path in &self.root_certs {
+            let cert = Certificate::from_pem(&read_file(path)?).map_err(TlsError::NativeTls)?;
+            builder.add_root_certificate(cert);
+        }
+        if let Some(path) = &self.client_identity {
+            let password = self.client_identity_password.as_deref().unwrap_or("");
+            let identity =
+                Identity::from_pkcs12(&read_file(path)?, password).map_err(TlsError::NativeTls)?;
+            builder.identity(identity);
+        }
+        let connector = builder.build().map_err(TlsError::NativeTls)?;
+        Ok(postgres_native_tls::MakeTlsConnector::new(connector))
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl TlsConfig {
+    /// Create a `MakeTlsConnect` implementation using `rustls`
+    pub fn make_rustls_connector(
+        &self,
+    ) -> Result<tokio_postgres_rustls::MakeRustlsConnect, TlsError> {
+        use rustls_crate::internal::pemfile;
+        let mut client_config = rustls_crate::ClientConfig::new();
+        client_config.root_store = match rustls_native_certs::load_native_certs() {
+            Ok(store) => store,
+            // Some of the system's certificates could not be parsed. Use
+            // the ones which could.
+            Err((Some(store), _)) => store,
+            Err((None, e)) => return Err(TlsError::NativeCerts(e)),
+        };
+        for path in &self.root_certs {
+            let pem = read_file(path)?;
+            match client_config.root_store.add_pem_file(&mut pem.as_slice()) {
+                Ok((valid, _)) if valid > 0 => {}
+                _ => return Err(TlsError::Invalid(path.clone())),
+            }
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert_path), Some(key_path)) => {
+                let pem = read_file(cert_path)?;
+                let certs = pemfile::certs(&mut pem.as_slice())
+                    .map_err(|_| TlsError::Invalid(cert_path.clone()))?;
+                let pem = read_file(key_path)?;
+                let mut keys = pemfile::pkcs8_private_keys(&mut pem.as_slice())
+                    .map_err(|_| TlsError::Invalid(key_path.clone()))?;
+                if keys.is_empty() {
+                    keys = pemfile::rsa_private_keys(&mut pem.as_slice())
+                        .map_err(|_| TlsError::Invalid(key_path.clone()))?;
+                }
+                let key = keys
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| TlsError::Invalid(key_path.clone()))?;
+                client_config
+                    .set_single_client_cert(certs, key)
+                    .map_err(TlsError::Rustls)?;
+            }
+            (None, None) => {}
+            _ => return Err(TlsError::IncompleteClientCert),
+        }
+        Ok(tokio_postgres_rustls::MakeRustlsConnect::new(client_config))
+    }
+}
+
+#[cfg(feature = "native-tls")]
+impl Manager<postgres_native_tls::MakeTlsConnector> {
+    /// Create manager using `PgConfig` and a `native-tls` connector which
+    /// is configured by the given `TlsConfig`
+    pub fn new_native_tls(config: PgConfig, tls_config: &TlsConfig) -> Result<Self, TlsError> {
+        Ok(Self::new(config, tls_config.make_native_tls_connector()?))
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl Manager<tokio_postgres_rustls::MakeRustlsConnect> {
+    /// Create manager using `PgConfig` and a `rustls` connector which is
+    /// configured by the given `TlsConfig`
+    pub fn new_rustls(config: PgConfig, tls_config: &TlsConfig) -> Result<Self, TlsError> {
+        Ok(Self::new(config, tls_config.make_rustls_connector()?))
+    }
+}
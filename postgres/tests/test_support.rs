@@ -0,0 +1,55 @@
+#![cfg(feature = "test-support")]
+
+use deadpool_postgres::test_support::TestDatabase;
+use deadpool_postgres::{ManagerConfig, RecyclingMethod};
+use testcontainers::clients::Cli;
+
+#[tokio::main]
+#[test]
+async fn test_recycle() {
+    let docker = Cli::default();
+    let db = TestDatabase::start(&docker);
+    let manager_config = ManagerConfig {
+        recycling_method: RecyclingMethod::Clean,
+        ..Default::default()
+    };
+    let pool = db.create_pool(manager_config, 1);
+    let client = pool.get().await.unwrap();
+    client
+        .batch_execute("SET application_name = 'test'")
+        .await
+        .unwrap();
+    drop(client);
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one("SHOW application_name", &[])
+        .await
+        .unwrap();
+    assert_ne!(row.get::<_, String>(0), "test");
+}
+
+#[tokio::main]
+#[test]
+async fn test_statement_cache() {
+    let docker = Cli::default();
+    let db = TestDatabase::start(&docker);
+    let pool = db.create_pool(ManagerConfig::default(), 1);
+    let mut client = pool.get().await.unwrap();
+    client.prepare("SELECT 1").await.unwrap();
+    drop(client);
+    let mut client = pool.get().await.unwrap();
+    client.prepare("SELECT 1").await.unwrap();
+    assert_eq!(client.statement_cache.stats().hits, 1);
+}
+
+#[tokio::main]
+#[test]
+async fn test_restart() {
+    let docker = Cli::default();
+    let db = TestDatabase::start(&docker);
+    let pool = db.create_pool(ManagerConfig::default(), 1);
+    let client = pool.get().await.unwrap();
+    drop(client);
+    db.container().stop();
+    assert!(pool.get().await.is_err());
+}
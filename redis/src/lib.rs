@@ -49,6 +49,8 @@ mod cmd_wrapper;
 pub use cmd_wrapper::{cmd, Cmd};
 mod pipeline_wrapper;
 pub use pipeline_wrapper::{pipe, Pipeline};
+mod sentinel;
+pub use sentinel::{SentinelConnection, SentinelManager, SentinelPool};
 
 /// A type alias for using `deadpool::Object` with `redis`
 pub struct Connection {
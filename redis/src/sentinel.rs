@@ -0,0 +1,274 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use futures::compat::Future01CompatExt;
+use log::warn;
+use redis::{
+    Client, ConnectionAddr, ConnectionInfo, ErrorKind, IntoConnectionInfo, RedisError, RedisResult,
+    Value,
+};
+
+use crate::{cmd, Connection};
+
+/// A type alias for using `deadpool::Pool` with `SentinelManager`
+pub type SentinelPool = deadpool::Pool<SentinelConnection, RedisError>;
+
+/// A connection created by the `SentinelManager`. It dereferences to
+/// `Connection` so it can be used with `cmd` and `pipe`.
+pub struct SentinelConnection {
+    conn: Connection,
+    /// Generation of the `SentinelManager` when the connection was created
+    generation: usize,
+}
+
+impl Deref for SentinelConnection {
+    type Target = Connection;
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl DerefMut for SentinelConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+/// The manager for creating and recycling connections to the current
+/// master of a Redis deployment which is monitored by Sentinel.
+///
+/// The address of the master is resolved via the sentinels before every
+/// new connection is created. When recycling a connection its role is
+/// checked using the `ROLE` command. If the server is no longer a master
+/// or the sentinels report a different master all connections which were
+/// created before are discarded when they are recycled the next time.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use deadpool_redis::{SentinelManager, SentinelPool};
+///
+/// let mgr = SentinelManager::new(
+///     vec!["redis://sentinel1:26379/", "redis://sentinel2:26379/"],
+///     "mymaster",
+/// )?;
+/// let pool = SentinelPool::new(mgr, 16);
+/// ```
+pub struct SentinelManager {
+    sentinels: Vec<Client>,
+    master_name: String,
+    db: i64,
+    passwd: Option<String>,
+    /// Address of the master which was resolved last
+    master: Mutex<Option<ConnectionAddr>>,
+    /// Incremented whenever a failover is detected
+    generation: AtomicUsize,
+}
+
+impl SentinelManager {
+    /// Create manager using the addresses of the sentinels and the name of
+    /// the monitored master
+    pub fn new<T: IntoConnectionInfo>(sentinels: Vec<T>, master_name: &str) -> RedisResult<Self> {
+        if sentinels.is_empty() {
+            return Err(RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "deadpool.redis: At least one sentinel is required",
+            )));
+        }
+        Ok(Self {
+            sentinels: sentinels
+                .into_iter()
+                .map(Client::open)
+                .collect::<RedisResult<_>>()?,
+            master_name: master_name.to_owned(),
+            db: 0,
+            passwd: None,
+            master: Mutex::new(None),
+            generation: AtomicUsize::new(0),
+        })
+    }
+    /// Select the given database on connections to the master
+    pub fn with_db(mut self, db: i64) -> Self {
+        self.db = db;
+        self
+    }
+    /// Authenticate connections to the master using the given password
+    pub fn with_password(mut self, passwd: &str) -> Self {
+        self.passwd = Some(passwd.to_owned());
+        self
+    }
+    /// Ask the sentinels one after another for the address of the master
+    async fn resolve_master(&self) -> RedisResult<ConnectionAddr> {
+        let mut last_error = None;
+        for sentinel in &self.sentinels {
+            match self.query_sentinel(sentinel).await {
+                Ok(addr) => return Ok(addr),
+                Err(e) => {
+                    warn!(target: "deadpool.redis", "Querying sentinel failed: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap())
+    }
+    async fn query_sentinel(&self, sentinel: &Client) -> RedisResult<ConnectionAddr> {
+        let mut conn = Connection {
+            conn: Some(sentinel.get_async_connection().compat().await?),
+        };
+        let master: Option<(String, u16)> = cmd("SENTINEL")
+            .arg("get-master-addr-by-name")
+            .arg(&self.master_name)
+            .query(&mut conn)
+            .await?;
+        master_addr(master)
+    }
+    /// Remember the resolved address of the master and detect a failover
+    /// if it differs from the previously resolved one
+    fn update_master(&self, generation: usize, addr: &ConnectionAddr) {
+        let changed = {
+            let mut master = self.master.lock().unwrap();
+            let changed = master.as_ref().map_or(false, |master| master != addr);
+            *master = Some(addr.clone());
+            changed
+        };
+        if changed {
+            self.failover_detected(generation);
+        }
+    }
+    /// Start a new generation of connections so all connections which were
+    /// created before are discarded
+    fn failover_detected(&self, generation: usize) {
+        warn!(target: "deadpool.redis", "Failover detected, discarding connections");
+        // Another connection may have detected the failover first
+        let _ = self.generation.compare_exchange(
+            generation,
+            generation + 1,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        );
+    }
+    /// Fail if the connection was created before a failover
+    fn check_generation(&self, generation: usize) -> RedisResult<()> {
+        if generation != self.generation.load(Ordering::Acquire) {
+            return Err(RedisError::from((
+                ErrorKind::IoError,
+                "deadpool.redis: Connection was created before a failover",
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn master_addr(master: Option<(String, u16)>) -> RedisResult<ConnectionAddr> {
+    match master {
+        Some((host, port)) => Ok(ConnectionAddr::Tcp(host, port)),
+        None => Err(RedisError::from((
+            ErrorKind::ResponseError,
+            "deadpool.redis: Sentinel does not know the master",
+        ))),
+    }
+}
+
+/// Check the reply of the `ROLE` command
+fn is_master(role: &[Value]) -> bool {
+    match role.first() {
+        Some(Value::Data(role)) => role.as_slice() == b"master",
+        Some(Value::Status(role)) => role == "master",
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<SentinelConnection, RedisError> for SentinelManager {
+    async fn create(&self) -> Result<SentinelConnection, RedisError> {
+        let generation = self.generation.load(Ordering::Acquire);
+        let addr = self.resolve_master().await?;
+        self.update_master(generation, &addr);
+        let client = Client::open(ConnectionInfo {
+            addr: Box::new(addr),
+            db: self.db,
+            passwd: self.passwd.clone(),
+        })?;
+        let conn = client.get_async_connection().compat().await?;
+        Ok(SentinelConnection {
+            conn: Connection { conn: Some(conn) },
+            generation: self.generation.load(Ordering::Acquire),
+        })
+    }
+    async fn recycle(&self, conn: &mut SentinelConnection) -> Result<(), RedisError> {
+        self.check_generation(conn.generation)?;
+        let role: Vec<Value> = cmd("ROLE").query(conn).await?;
+        if is_master(&role) {
+            Ok(())
+        } else {
+            self.failover_detected(conn.generation);
+            Err(RedisError::from((
+                ErrorKind::IoError,
+                "deadpool.redis: Server is no longer the master",
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> SentinelManager {
+        SentinelManager::new(vec!["redis://127.0.0.1:26379/"], "mymaster").unwrap()
+    }
+
+    #[test]
+    fn test_is_master() {
+        let master = vec![
+            Value::Data(b"master".to_vec()),
+            Value::Int(3129659),
+            Value::Bulk(vec![]),
+        ];
+        assert!(is_master(&master));
+        let replica = vec![
+            Value::Data(b"slave".to_vec()),
+            Value::Data(b"127.0.0.1".to_vec()),
+            Value::Int(9999),
+            Value::Data(b"connected".to_vec()),
+            Value::Int(3167038),
+        ];
+        assert!(!is_master(&replica));
+        assert!(!is_master(&[Value::Data(b"sentinel".to_vec())]));
+        assert!(!is_master(&[]));
+    }
+
+    #[test]
+    fn test_master_addr() {
+        let addr = master_addr(Some(("10.0.0.1".to_string(), 6380))).unwrap();
+        assert_eq!(addr, ConnectionAddr::Tcp("10.0.0.1".to_string(), 6380));
+        let e = master_addr(None).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::ResponseError);
+    }
+
+    #[test]
+    fn test_failover_detected() {
+        let mgr = manager();
+        assert!(mgr.check_generation(0).is_ok());
+        mgr.failover_detected(0);
+        assert!(mgr.check_generation(0).is_err());
+        assert!(mgr.check_generation(1).is_ok());
+        // Connections of the old generation detecting the same failover
+        // must not start yet another generation.
+        mgr.failover_detected(0);
+        assert!(mgr.check_generation(1).is_ok());
+    }
+
+    #[test]
+    fn test_master_changed() {
+        let mgr = manager();
+        let master = ConnectionAddr::Tcp("10.0.0.1".to_string(), 6379);
+        mgr.update_master(0, &master);
+        mgr.update_master(0, &master);
+        assert!(mgr.check_generation(0).is_ok());
+        mgr.update_master(0, &ConnectionAddr::Tcp("10.0.0.2".to_string(), 6379));
+        assert!(mgr.check_generation(1).is_ok());
+    }
+}
@@ -0,0 +1,57 @@
+use deadpool::PoolConfig;
+use serde::Deserialize;
+
+use crate::{Manager, Pool};
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// Addresses (`host:port`) of the known nodes. Defaults to
+    /// `127.0.0.1:9042`.
+    pub nodes: Option<Vec<String>>,
+    /// See `Manager::with_local_datacenter`
+    pub local_datacenter: Option<String>,
+    /// See `Manager::with_keyspace`
+    pub keyspace: Option<String>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `SCYLLA__LOCAL_DATACENTER` and `SCYLLA__POOL__MAX_SIZE`
+    /// for the prefix `SCYLLA`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create manager using this configuration object
+    pub fn create_manager(&self) -> Manager {
+        let nodes = self
+            .nodes
+            .clone()
+            .unwrap_or_else(|| vec!["127.0.0.1:9042".to_string()]);
+        let mut manager = Manager::new(nodes);
+        if let Some(local_datacenter) = &self.local_datacenter {
+            manager = manager.with_local_datacenter(local_datacenter);
+        }
+        if let Some(keyspace) = &self.keyspace {
+            manager = manager.with_keyspace(keyspace);
+        }
+        manager
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Pool {
+        Pool::from_config(self.create_manager(), self.pool.clone().unwrap_or_default())
+    }
+}
@@ -0,0 +1,191 @@
+//! Deadpool simple async pool for CQL sessions.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`scylla`](https://crates.io/crates/scylla) which works
+//! with ScyllaDB and Apache Cassandra. The pooled `Session` wrapper
+//! provides a statement cache for prepared statements.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_scylla::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::new(vec!["127.0.0.1:9042".to_string()])
+//!         .with_local_datacenter("datacenter1");
+//!     let pool = Pool::new(mgr, 4);
+//!     let mut session = pool.get().await.unwrap();
+//!     session
+//!         .execute_cached("SELECT release_version FROM system.local", &[])
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use scylla::frame::value::ValueList;
+use scylla::prepared_statement::PreparedStatement;
+use scylla::transport::errors::{NewSessionError, QueryError};
+use scylla::transport::load_balancing::{DcAwareRoundRobinPolicy, TokenAwarePolicy};
+use scylla::{QueryResult, SessionBuilder};
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with `scylla`
+pub type Pool = deadpool::Pool<Session, Error>;
+
+/// A type alias for using `deadpool::Object` with `scylla`
+pub type Object = deadpool::Object<Session, Error>;
+
+/// Query which is used for checking the health of a session
+const HEARTBEAT_QUERY: &str = "SELECT now() FROM system.local";
+
+/// This error is returned by the `Manager` and the methods of `Session`
+#[derive(Debug)]
+pub enum Error {
+    /// A session could not be created
+    Connect(NewSessionError),
+    /// A query failed
+    Query(QueryError),
+}
+
+impl From<NewSessionError> for Error {
+    fn from(e: NewSessionError) -> Self {
+        Error::Connect(e)
+    }
+}
+
+impl From<QueryError> for Error {
+    fn from(e: QueryError) -> Self {
+        Error::Query(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Connect(e) => write!(f, "Creating session failed: {}", e),
+            Error::Query(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The manager for creating and recyling CQL sessions
+pub struct Manager {
+    known_nodes: Vec<String>,
+    local_datacenter: Option<String>,
+    keyspace: Option<String>,
+}
+
+impl Manager {
+    /// Create manager using the addresses (`host:port`) of one or more
+    /// nodes of the cluster. The other nodes are discovered automatically.
+    pub fn new(known_nodes: Vec<String>) -> Self {
+        Manager {
+            known_nodes,
+            local_datacenter: None,
+            keyspace: None,
+        }
+    }
+    /// Prefer the nodes of the given datacenter. Requests are routed to
+    /// nodes of other datacenters only if no local node is available.
+    pub fn with_local_datacenter(mut self, local_datacenter: &str) -> Self {
+        self.local_datacenter = Some(local_datacenter.to_owned());
+        self
+    }
+    /// Use the given keyspace for all sessions
+    pub fn with_keyspace(mut self, keyspace: &str) -> Self {
+        self.keyspace = Some(keyspace.to_owned());
+        self
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Session, Error> for Manager {
+    async fn create(&self) -> Result<Session, Error> {
+        let mut builder = SessionBuilder::new().known_nodes(&self.known_nodes);
+        if let Some(local_datacenter) = &self.local_datacenter {
+            let dc_policy = Box::new(DcAwareRoundRobinPolicy::new(local_datacenter.clone()));
+            builder = builder.load_balancing(Arc::new(TokenAwarePolicy::new(dc_policy)));
+        }
+        if let Some(keyspace) = &self.keyspace {
+            builder = builder.use_keyspace(keyspace, false);
+        }
+        Ok(Session::new(builder.build().await?))
+    }
+    async fn recycle(&self, session: &mut Session) -> Result<(), Error> {
+        session.session.query(HEARTBEAT_QUERY, &[]).await?;
+        Ok(())
+    }
+}
+
+/// A wrapper for `scylla::Session` which includes a statement cache.
+pub struct Session {
+    session: scylla::Session,
+    statement_cache: HashMap<String, PreparedStatement>,
+}
+
+impl Session {
+    fn new(session: scylla::Session) -> Self {
+        Session {
+            session,
+            statement_cache: HashMap::new(),
+        }
+    }
+    /// Creates a new prepared statement using the statement cache if
+    /// possible.
+    pub async fn prepare_cached(&mut self, query: &str) -> Result<PreparedStatement, Error> {
+        if let Some(statement) = self.statement_cache.get(query) {
+            return Ok(statement.clone());
+        }
+        let statement = self.session.prepare(query).await?;
+        self.statement_cache
+            .insert(query.to_owned(), statement.clone());
+        Ok(statement)
+    }
+    /// Prepare the statement using the statement cache and execute it
+    pub async fn execute_cached(
+        &mut self,
+        query: &str,
+        values: impl ValueList,
+    ) -> Result<QueryResult, Error> {
+        let statement = self.prepare_cached(query).await?;
+        Ok(self.session.execute(&statement, values).await?)
+    }
+    /// Retrieve current size of the statement cache
+    pub fn statement_cache_size(&self) -> usize {
+        self.statement_cache.len()
+    }
+    /// Clear the statement cache
+    pub fn clear_statement_cache(&mut self) {
+        self.statement_cache.clear();
+    }
+}
+
+impl Deref for Session {
+    type Target = scylla::Session;
+    fn deref(&self) -> &scylla::Session {
+        &self.session
+    }
+}
+
+impl DerefMut for Session {
+    fn deref_mut(&mut self) -> &mut scylla::Session {
+        &mut self.session
+    }
+}
@@ -0,0 +1,25 @@
+use std::env;
+
+use deadpool_scylla::{Manager, Pool};
+
+fn create_pool() -> Pool {
+    let node = env::var("SCYLLA_NODE").unwrap_or_else(|_| "127.0.0.1:9042".into());
+    Pool::new(Manager::new(vec![node]), 4)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool();
+    let mut session = pool.get().await.unwrap();
+    let result = session
+        .execute_cached("SELECT release_version FROM system.local", &[])
+        .await
+        .unwrap();
+    assert!(result.rows.is_some());
+    session
+        .prepare_cached("SELECT release_version FROM system.local")
+        .await
+        .unwrap();
+    assert_eq!(session.statement_cache_size(), 1);
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use deadpool::PoolConfig;
+use serde::Deserialize;
+
+use crate::{Manager, ManagerConfig, Pool};
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// Path of the database file
+    pub path: PathBuf,
+    /// Manager configuration
+    pub manager: Option<ManagerConfig>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object for the database file at the given path
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Config {
+            path: path.into(),
+            manager: None,
+            pool: None,
+        }
+    }
+    /// Create manager using this configuration object
+    pub fn create_manager(&self) -> Manager {
+        Manager::from_config(&self.path, self.manager.clone().unwrap_or_default())
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Pool {
+        Pool::from_config(self.create_manager(), self.pool.clone().unwrap_or_default())
+    }
+}
@@ -0,0 +1,248 @@
+//! Deadpool simple async pool for SQLite connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`rusqlite`](https://crates.io/crates/rusqlite).
+//! Connections are created, recycled and used on the blocking thread pool
+//! of `tokio` so they never block the async executor.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_sqlite::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::new("deadpool.db");
+//!     let pool = Pool::new(mgr, 16);
+//!     for i in 1..10 {
+//!         let conn = pool.get().await.unwrap();
+//!         let value: i32 = conn
+//!             .interact(move |conn| {
+//!                 let mut stmt = conn.prepare_cached("SELECT 1 + ?")?;
+//!                 stmt.query_row(&[&i], |row| row.get(0))
+//!             })
+//!             .await
+//!             .unwrap()
+//!             .unwrap();
+//!         assert_eq!(value, i + 1);
+//!     }
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::task::spawn_blocking;
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with `rusqlite`
+pub type Pool = deadpool::Pool<Connection, Error>;
+
+/// A type alias for using `deadpool::Object` with `rusqlite`
+pub type Object = deadpool::Object<Connection, Error>;
+
+/// This error is returned by the `Manager` and `Connection::interact`
+#[derive(Debug)]
+pub enum Error {
+    /// The `rusqlite` connection returned an error
+    Backend(rusqlite::Error),
+    /// The closure passed to `Connection::interact` panicked
+    Panic,
+    /// The connection is unusable because a previous interaction panicked
+    Poisoned,
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Backend(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Backend(e) => write!(f, "{}", e),
+            Error::Panic => write!(f, "Interaction with the connection panicked"),
+            Error::Poisoned => write!(f, "Connection is poisoned by a previous panic"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// This enum is used to control the `journal_mode` pragma of new
+/// connections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+pub enum JournalMode {
+    /// Keep the journal mode of the database file
+    Default,
+    /// Use a rollback journal which is deleted at the end of every
+    /// transaction
+    Delete,
+    /// Use a write-ahead log. Readers do not block writers and a writer does
+    /// not block readers which suits a pool of connections. This is the
+    /// default.
+    Wal,
+}
+
+impl Default for JournalMode {
+    fn default() -> Self {
+        JournalMode::Wal
+    }
+}
+
+/// Configuration object for the `Manager`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct ManagerConfig {
+    /// The `journal_mode` of new connections. Defaults to `Wal`.
+    pub journal_mode: JournalMode,
+    /// Time to wait for locks held by other connections before failing
+    /// with `SQLITE_BUSY`. Defaults to 5 seconds. Given in milliseconds
+    /// when read from a configuration file or environment variables.
+    #[cfg_attr(
+        feature = "config",
+        serde(deserialize_with = "deadpool::serde_duration::millis")
+    )]
+    pub busy_timeout: Option<Duration>,
+    /// Set the `synchronous` pragma to `NORMAL` which is safe when using a
+    /// write-ahead log. Defaults to `true`.
+    pub synchronous_normal: bool,
+    /// Enable the `foreign_keys` pragma. Defaults to `true`.
+    pub foreign_keys: bool,
+    /// Additional pragmas which are set on every new connection, e.g.
+    /// `("cache_size", "-20000")`.
+    pub pragmas: Vec<(String, String)>,
+}
+
+impl Default for ManagerConfig {
+    fn default() -> Self {
+        ManagerConfig {
+            journal_mode: JournalMode::default(),
+            busy_timeout: Some(Duration::from_secs(5)),
+            synchronous_normal: true,
+            foreign_keys: true,
+            pragmas: Vec::new(),
+        }
+    }
+}
+
+impl ManagerConfig {
+    /// Apply the pragmas to the given connection
+    fn apply(&self, conn: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
+        if let Some(busy_timeout) = self.busy_timeout {
+            conn.busy_timeout(busy_timeout)?;
+        }
+        match self.journal_mode {
+            JournalMode::Default => {}
+            // `journal_mode` returns the new mode as a row
+            JournalMode::Delete => {
+                conn.pragma_update_and_check(None, "journal_mode", &"DELETE", |_| Ok(()))?
+            }
+            JournalMode::Wal => {
+                conn.pragma_update_and_check(None, "journal_mode", &"WAL", |_| Ok(()))?
+            }
+        }
+        if self.synchronous_normal {
+            conn.pragma_update(None, "synchronous", &"NORMAL")?;
+        }
+        if self.foreign_keys {
+            conn.pragma_update(None, "foreign_keys", &true)?;
+        }
+        for (name, value) in &self.pragmas {
+            conn.pragma_update(None, name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The pooled object which provides access to the `rusqlite::Connection`
+/// via `Connection::interact`.
+pub struct Connection {
+    inner: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Connection {
+    /// Run the given closure on the blocking thread pool passing it the
+    /// wrapped connection and return its result.
+    ///
+    /// If the closure panics `Error::Panic` is returned. The object is still
+    /// returned to the pool, but the panic poisons the connection so the
+    /// next `recycle` fails with `Error::Poisoned` and discards it.
+    pub async fn interact<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut rusqlite::Connection) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let inner = self.inner.clone();
+        spawn_blocking(move || match inner.lock() {
+            Ok(mut conn) => Ok(f(&mut conn)),
+            Err(_) => Err(Error::Poisoned),
+        })
+        .await
+        .map_err(|_| Error::Panic)?
+    }
+}
+
+/// The manager for creating and recyling SQLite connections
+pub struct Manager {
+    path: PathBuf,
+    config: ManagerConfig,
+}
+
+impl Manager {
+    /// Create manager for the database file at the given path
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self::from_config(path, ManagerConfig::default())
+    }
+    /// Create manager for the database file at the given path using the
+    /// given `ManagerConfig`
+    pub fn from_config<P: AsRef<Path>>(path: P, config: ManagerConfig) -> Self {
+        Manager {
+            path: path.as_ref().to_owned(),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Connection, Error> for Manager {
+    async fn create(&self) -> Result<Connection, Error> {
+        let path = self.path.clone();
+        let config = self.config.clone();
+        let conn = spawn_blocking(move || {
+            let conn = rusqlite::Connection::open(path)?;
+            config.apply(&conn)?;
+            Ok::<_, rusqlite::Error>(conn)
+        })
+        .await
+        .map_err(|_| Error::Panic)??;
+        Ok(Connection {
+            inner: Arc::new(Mutex::new(conn)),
+        })
+    }
+    async fn recycle(&self, conn: &mut Connection) -> Result<(), Error> {
+        conn.interact(|conn| {
+            // Roll back transactions which were left open
+            if !conn.is_autocommit() {
+                conn.execute_batch("ROLLBACK")?;
+            }
+            Ok::<_, rusqlite::Error>(())
+        })
+        .await??;
+        Ok(())
+    }
+}
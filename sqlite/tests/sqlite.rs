@@ -0,0 +1,72 @@
+use std::env;
+
+use deadpool_sqlite::{Manager, ManagerConfig, Pool};
+
+fn create_pool(name: &str, manager_config: ManagerConfig) -> Pool {
+    let path = env::temp_dir().join(format!("deadpool-sqlite-{}.db", name));
+    let _ = std::fs::remove_file(&path);
+    Pool::new(Manager::from_config(path, manager_config), 16)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool("basic", ManagerConfig::default());
+    let conn = pool.get().await.unwrap();
+    let value: i32 = conn
+        .interact(|conn| {
+            let mut stmt = conn.prepare_cached("SELECT 1 + 2")?;
+            stmt.query_row(rusqlite::NO_PARAMS, |row| row.get(0))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value, 3);
+}
+
+#[tokio::main]
+#[test]
+async fn test_wal() {
+    let pool = create_pool("wal", ManagerConfig::default());
+    let conn = pool.get().await.unwrap();
+    let journal_mode: String = conn
+        .interact(|conn| conn.pragma_query_value(None, "journal_mode", |row| row.get(0)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(journal_mode, "wal");
+}
+
+#[tokio::main]
+#[test]
+async fn test_pragmas() {
+    let pool = create_pool(
+        "pragmas",
+        ManagerConfig {
+            pragmas: vec![("user_version".to_string(), "42".to_string())],
+            ..Default::default()
+        },
+    );
+    let conn = pool.get().await.unwrap();
+    let user_version: i32 = conn
+        .interact(|conn| conn.pragma_query_value(None, "user_version", |row| row.get(0)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_version, 42);
+}
+
+#[tokio::main]
+#[test]
+async fn test_rollback_on_recycle() {
+    let pool = create_pool("rollback", ManagerConfig::default());
+    let conn = pool.get().await.unwrap();
+    conn.interact(|conn| conn.execute_batch("BEGIN"))
+        .await
+        .unwrap()
+        .unwrap();
+    drop(conn);
+    let conn = pool.get().await.unwrap();
+    let autocommit = conn.interact(|conn| conn.is_autocommit()).await.unwrap();
+    assert!(autocommit);
+}
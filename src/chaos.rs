@@ -0,0 +1,110 @@
+//! Manager wrapper for injecting failures and latencies
+//!
+//! The `ChaosManager` wraps any `Manager` and makes `create` and `recycle`
+//! fail every Nth time and/or delays them. This makes it possible to test
+//! the resilience logic of an application deterministically.
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::timer::Timer;
+use crate::Manager;
+
+/// Configuration of the injected failures and latencies
+#[derive(Clone, Debug, Default)]
+pub struct ChaosConfig {
+    /// Make every Nth call to `create` fail
+    pub create_fail_every: Option<usize>,
+    /// Make every Nth call to `recycle` fail
+    pub recycle_fail_every: Option<usize>,
+    /// Delay every call to `create`. Requires a timer.
+    pub create_delay: Option<Duration>,
+    /// Delay every call to `recycle`. Requires a timer. Combined with a
+    /// timeout this can be used to simulate recycling timing out.
+    pub recycle_delay: Option<Duration>,
+}
+
+/// A `Manager` which wraps another manager and injects failures and
+/// latencies as configured by `ChaosConfig`.
+pub struct ChaosManager<M, T, E> {
+    manager: M,
+    config: ChaosConfig,
+    error: Box<dyn Fn() -> E + Send + Sync>,
+    timer: Option<Box<dyn Timer>>,
+    create_count: AtomicUsize,
+    recycle_count: AtomicUsize,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<M, T, E> ChaosManager<M, T, E> {
+    /// Wrap `manager` using the given `config`. The `error` function is
+    /// called to create the errors returned for injected failures.
+    pub fn new(
+        manager: M,
+        config: ChaosConfig,
+        error: impl Fn() -> E + Send + Sync + 'static,
+    ) -> Self {
+        ChaosManager {
+            manager,
+            config,
+            error: Box::new(error),
+            timer: None,
+            create_count: AtomicUsize::new(0),
+            recycle_count: AtomicUsize::new(0),
+            _phantom: PhantomData,
+        }
+    }
+    /// Set the timer used for the configured delays. Without a timer
+    /// the delays are ignored.
+    pub fn with_timer(mut self, timer: impl Timer + 'static) -> Self {
+        self.timer = Some(Box::new(timer));
+        self
+    }
+    /// Number of calls to `create` so far
+    pub fn create_count(&self) -> usize {
+        self.create_count.load(Ordering::Relaxed)
+    }
+    /// Number of calls to `recycle` so far
+    pub fn recycle_count(&self) -> usize {
+        self.recycle_count.load(Ordering::Relaxed)
+    }
+    async fn delay(&self, delay: Option<Duration>) {
+        if let (Some(timer), Some(delay)) = (&self.timer, delay) {
+            timer.sleep(delay).await;
+        }
+    }
+}
+
+fn should_fail(count: usize, every: Option<usize>) -> bool {
+    match every {
+        Some(every) if every > 0 => count % every == 0,
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl<M, T, E> Manager<T, E> for ChaosManager<M, T, E>
+where
+    M: Manager<T, E> + Send + Sync,
+    T: Send,
+    E: Send,
+{
+    async fn create(&self) -> Result<T, E> {
+        let count = self.create_count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.delay(self.config.create_delay).await;
+        if should_fail(count, self.config.create_fail_every) {
+            return Err((self.error)());
+        }
+        self.manager.create().await
+    }
+    async fn recycle(&self, obj: &mut T) -> Result<(), E> {
+        let count = self.recycle_count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.delay(self.config.recycle_delay).await;
+        if should_fail(count, self.config.recycle_fail_every) {
+            return Err((self.error)());
+        }
+        self.manager.recycle(obj).await
+    }
+}
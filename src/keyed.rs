@@ -0,0 +1,162 @@
+//! Keyed pools
+//!
+//! A `KeyedPool` lazily creates one sub-pool per key (e.g. a tenant id or
+//! a hostname). Sub-pools which have not been used for a while can be
+//! evicted as a whole.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use crate::{Object, Pool};
+
+struct Entry<T, E> {
+    pool: Pool<T, E>,
+    /// Value of `KeyedPoolInner::generation` when this sub-pool was used
+    /// the last time.
+    last_used: u64,
+}
+
+impl<T, E> Entry<T, E> {
+    fn size(&self) -> usize {
+        self.pool.inner.size.load(Ordering::Relaxed)
+    }
+    /// A sub-pool is idle if none of its objects are currently in use.
+    fn is_idle(&self) -> bool {
+        let size = self.pool.inner.size.load(Ordering::Relaxed) as isize;
+        self.pool.inner.available.load(Ordering::Relaxed) >= size
+    }
+}
+
+struct KeyedPoolInner<K, T, E> {
+    pools: HashMap<K, Entry<T, E>>,
+    /// Logical clock which is advanced by every call to `get` and
+    /// `evict_idle`. This avoids `Instant` which is not available on all
+    /// targets.
+    generation: u64,
+    last_eviction: u64,
+}
+
+/// A pool which lazily creates a sub-pool per key.
+///
+/// The `factory` is called with the key whenever a sub-pool needs to be
+/// created. The configuration of the returned pool (e.g. its `max_size`)
+/// acts as per-key limit.
+pub struct KeyedPool<K, T, E> {
+    factory: Box<dyn Fn(&K) -> Pool<T, E> + Send + Sync>,
+    max_size: Option<usize>,
+    inner: Mutex<KeyedPoolInner<K, T, E>>,
+}
+
+impl<K, T, E> KeyedPool<K, T, E>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Create a new keyed pool using the given `factory` for creating
+    /// sub-pools.
+    pub fn new(factory: impl Fn(&K) -> Pool<T, E> + Send + Sync + 'static) -> Self {
+        KeyedPool {
+            factory: Box::new(factory),
+            max_size: None,
+            inner: Mutex::new(KeyedPoolInner {
+                pools: HashMap::new(),
+                generation: 0,
+                last_eviction: 0,
+            }),
+        }
+    }
+    /// Create a new keyed pool with a global `max_size`.
+    ///
+    /// The global limit is enforced by evicting idle sub-pools (least
+    /// recently used first) before a sub-pool without available objects
+    /// is asked for a new one. If all sub-pools are in use the limit can
+    /// be exceeded by up to the per-key limit of the requested sub-pool.
+    pub fn with_max_size(
+        factory: impl Fn(&K) -> Pool<T, E> + Send + Sync + 'static,
+        max_size: usize,
+    ) -> Self {
+        let mut pool = Self::new(factory);
+        pool.max_size = Some(max_size);
+        pool
+    }
+    /// Retrieve object from the sub-pool for `key`. The sub-pool is created
+    /// if it does not exist, yet.
+    pub async fn get(&self, key: &K) -> Result<Object<T, E>, E> {
+        let pool = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.generation += 1;
+            let generation = inner.generation;
+            if !inner.pools.contains_key(key) {
+                let pool = (self.factory)(key);
+                inner.pools.insert(
+                    key.clone(),
+                    Entry {
+                        pool,
+                        last_used: generation,
+                    },
+                );
+            }
+            let entry = inner.pools.get_mut(key).unwrap();
+            entry.last_used = generation;
+            let pool = entry.pool.clone();
+            if let Some(max_size) = self.max_size {
+                if pool.inner.available.load(Ordering::Relaxed) <= 0 {
+                    Self::make_room(&mut inner, key, max_size);
+                }
+            }
+            pool
+        };
+        pool.get().await
+    }
+    /// Evict idle sub-pools until the total number of objects is below
+    /// `max_size`.
+    fn make_room(inner: &mut KeyedPoolInner<K, T, E>, key: &K, max_size: usize) {
+        let mut total: usize = inner.pools.values().map(Entry::size).sum();
+        if total < max_size {
+            return;
+        }
+        let mut candidates: Vec<(u64, K)> = inner
+            .pools
+            .iter()
+            .filter(|(k, entry)| *k != key && entry.is_idle() && entry.size() > 0)
+            .map(|(k, entry)| (entry.last_used, k.clone()))
+            .collect();
+        candidates.sort_by_key(|(last_used, _)| *last_used);
+        for (_, k) in candidates {
+            if total < max_size {
+                break;
+            }
+            if let Some(entry) = inner.pools.remove(&k) {
+                total -= entry.size();
+            }
+        }
+    }
+    /// Evict all sub-pools which are idle and have not been used since the
+    /// last call to this method. Call this method periodically to get rid
+    /// of sub-pools which are no longer needed. The number of evicted
+    /// sub-pools is returned.
+    pub fn evict_idle(&self) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let last_eviction = inner.last_eviction;
+        let before = inner.pools.len();
+        inner
+            .pools
+            .retain(|_, entry| entry.last_used > last_eviction || !entry.is_idle());
+        inner.last_eviction = inner.generation;
+        before - inner.pools.len()
+    }
+    /// Remove the sub-pool for the given `key`. Objects which are
+    /// currently in use are dropped once they are returned.
+    pub fn remove(&self, key: &K) -> Option<Pool<T, E>> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pools.remove(key).map(|entry| entry.pool)
+    }
+    /// Retrieve the number of sub-pools
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().pools.len()
+    }
+    /// Returns `true` if no sub-pool exists
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
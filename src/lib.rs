@@ -62,7 +62,15 @@ use std::sync::{Arc, Weak};
 
 use async_trait::async_trait;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
+
+pub mod chaos;
+pub mod keyed;
+#[cfg(feature = "config")]
+pub mod serde_duration;
+pub mod timer;
+
+pub use keyed::KeyedPool;
 
 /// This trait is used to `create` new objects or `recycle` existing ones.
 #[async_trait]
@@ -99,6 +107,20 @@ impl<T, E> Object<T, E> {
             pool: Arc::downgrade(&pool.inner),
         }
     }
+    /// Take the wrapped object out of the pool. The object will not be
+    /// returned to the pool and the pool size is reduced by one, making room
+    /// for a new object to be created.
+    ///
+    /// This is an associated function in order to avoid conflicts with
+    /// methods of `T`. Use it as `Object::take(obj)`.
+    pub fn take(mut this: Self) -> T {
+        if let Some(pool) = this.pool.upgrade() {
+            pool.size.fetch_sub(1, Ordering::Relaxed);
+        }
+        // Detach from the pool so `drop` does not touch the pool state.
+        this.pool = Weak::new();
+        this.obj.take().unwrap()
+    }
 }
 
 impl<T, E> Drop for Object<T, E> {
@@ -150,6 +172,8 @@ impl<T, E> DerefMut for Object<T, E> {
 struct PoolInner<T, E> {
     manager: Box<dyn Manager<T, E> + Sync + Send>,
     max_size: usize,
+    /// Limits the number of objects being created at the same time
+    create_semaphore: Option<Semaphore>,
     obj_sender: Sender<Option<T>>,
     obj_receiver: Mutex<Receiver<Option<T>>>,
     size: AtomicUsize,
@@ -160,6 +184,24 @@ struct PoolInner<T, E> {
 }
 
 impl<T, E> PoolInner<T, E> {
+    /// Receive an object from the channel without waiting for one
+    fn try_recv(&self) -> Option<Option<T>> {
+        self.obj_receiver.try_lock().ok()?.try_recv().ok()
+    }
+    /// Increase `available` for an object which is about to be created.
+    /// Returns `false` if there are already enough objects for all waiting
+    /// futures.
+    fn reserve_create(&self) -> bool {
+        self.available
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |available| {
+                if available < 0 {
+                    Some(available + 1)
+                } else {
+                    None
+                }
+            })
+            .is_ok()
+    }
     fn return_obj(&self, obj: Option<T>) {
         match self.obj_sender.clone().try_send(obj) {
             Ok(_) => {
@@ -185,6 +227,41 @@ pub struct Pool<T, E> {
     inner: Arc<PoolInner<T, E>>,
 }
 
+/// Pool configuration
+///
+/// If the `config` feature is enabled this struct implements
+/// `serde::Deserialize` so it can be read from configuration files and
+/// environment variables.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct PoolConfig {
+    /// Maximum number of objects ever created
+    pub max_size: usize,
+    /// Maximum number of objects being created at the same time. This
+    /// prevents a cold pool from opening `max_size` connections at once
+    /// when receiving a burst of requests. `None` means no limit. `Some(0)`
+    /// is treated like `None` as no object could ever be created.
+    pub max_concurrent_creates: Option<usize>,
+}
+
+impl PoolConfig {
+    /// Create pool config without any limit on concurrent creates
+    pub fn new(max_size: usize) -> Self {
+        PoolConfig {
+            max_size,
+            max_concurrent_creates: None,
+        }
+    }
+}
+
+impl Default for PoolConfig {
+    /// Create pool config with a `max_size` of 16
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
 #[derive(Debug)]
 /// The current pool status.
 pub struct Status {
@@ -205,10 +282,21 @@ impl<T, E> Pool<T, E> {
     /// The `manager` is used to create and recycle objects and `max_size`
     /// is the maximum number of objects ever created.
     pub fn new(manager: impl Manager<T, E> + Send + Sync + 'static, max_size: usize) -> Pool<T, E> {
-        let (obj_sender, obj_receiver) = channel::<Option<T>>(max_size);
+        Self::from_config(manager, PoolConfig::new(max_size))
+    }
+    /// Create new connection pool with a given `manager` and `config`.
+    pub fn from_config(
+        manager: impl Manager<T, E> + Send + Sync + 'static,
+        config: PoolConfig,
+    ) -> Pool<T, E> {
+        let (obj_sender, obj_receiver) = channel::<Option<T>>(config.max_size);
         Pool {
             inner: Arc::new(PoolInner {
-                max_size: max_size,
+                max_size: config.max_size,
+                create_semaphore: config
+                    .max_concurrent_creates
+                    .filter(|&n| n > 0)
+                    .map(Semaphore::new),
                 manager: Box::new(manager),
                 obj_sender: obj_sender,
                 obj_receiver: Mutex::new(obj_receiver),
@@ -223,20 +311,36 @@ impl<T, E> Pool<T, E> {
         let mut size = self.inner.size.load(Ordering::Relaxed);
         let mut obj = Object::new(&self);
         loop {
+            let mut received = None;
             if available <= 0 && size < self.inner.max_size {
                 // The pool is empty and the max size has not been
-                // reached, yet.
-                if self.inner.size.fetch_add(1, Ordering::Relaxed) < self.inner.max_size {
-                    self.inner.available.fetch_add(1, Ordering::Relaxed);
-                    obj.state = ObjectState::Creating;
-                    obj.obj = Some(self.inner.manager.create().await?);
-                    obj.state = ObjectState::Ready;
-                    break;
-                } else {
-                    self.inner.size.fetch_sub(1, Ordering::Relaxed);
+                // reached, yet. The permit is acquired before reserving a
+                // slot so waiting for it does not count towards the size
+                // of the pool.
+                let _permit = match &self.inner.create_semaphore {
+                    Some(semaphore) => Some(semaphore.acquire().await),
+                    None => None,
+                };
+                // Objects might have been returned or created while
+                // waiting for the permit. Use those instead of creating a
+                // new object.
+                received = self.inner.try_recv();
+                if received.is_none() && self.inner.reserve_create() {
+                    if self.inner.size.fetch_add(1, Ordering::Relaxed) < self.inner.max_size {
+                        obj.state = ObjectState::Creating;
+                        obj.obj = Some(self.inner.manager.create().await?);
+                        obj.state = ObjectState::Ready;
+                        break;
+                    } else {
+                        self.inner.size.fetch_sub(1, Ordering::Relaxed);
+                        self.inner.available.fetch_sub(1, Ordering::Relaxed);
+                    }
                 }
             }
-            let inner_obj = self.inner.obj_receiver.lock().await.recv().await.unwrap();
+            let inner_obj = match received {
+                Some(inner_obj) => inner_obj,
+                None => self.inner.obj_receiver.lock().await.recv().await.unwrap(),
+            };
             if let Some(inner_obj) = inner_obj {
                 obj.obj = Some(inner_obj);
                 obj.state = ObjectState::Recycling;
@@ -255,6 +359,20 @@ impl<T, E> Pool<T, E> {
         }
         Ok(obj)
     }
+    /// Make sure that at least `n` objects can be created by retrieving
+    /// them from the pool at the same time. The objects are returned to the
+    /// pool afterwards. This is useful for failing fast at startup (e.g. due
+    /// to misconfigured credentials) instead of on the first call to `get`.
+    ///
+    /// `n` is capped at the `max_size` of the pool.
+    pub async fn ensure_connected(&self, n: usize) -> Result<(), E> {
+        let n = n.min(self.inner.max_size);
+        let mut objs = Vec::with_capacity(n);
+        for _ in 0..n {
+            objs.push(self.get().await?);
+        }
+        Ok(())
+    }
     /// Retrieve status of the pool
     pub fn status(&self) -> Status {
         let size = self.inner.size.load(Ordering::Relaxed);
@@ -0,0 +1,30 @@
+//! Deserialization of durations from configuration files and environment
+//! variables
+//!
+//! `Duration` itself deserializes from a struct of `secs` and `nanos`,
+//! which can not be expressed using a single environment variable. The
+//! configuration structs of the deadpool crates use this module so their
+//! timeouts are given as a number of milliseconds instead, e.g.
+//! `PG__MANAGER__CREATE_TIMEOUT=5000`.
+//!
+//! Missing fields are only accepted if either the field or the struct is
+//! annotated with `#[serde(default)]`.
+//!
+//! ```rust,ignore
+//! #[derive(serde::Deserialize)]
+//! struct Config {
+//!     #[serde(default, deserialize_with = "deadpool::serde_duration::millis")]
+//!     timeout: Option<Duration>,
+//! }
+//! ```
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
+/// Deserialize an optional `Duration` from a number of milliseconds
+pub fn millis<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+}
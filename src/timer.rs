@@ -0,0 +1,88 @@
+//! Runtime-neutral sleep and timeout abstraction
+//!
+//! Deadpool itself never spawns futures and therefore does not depend on
+//! any specific runtime. Features which need to wait for some time (e.g.
+//! timeouts and backoff) use the `Timer` trait instead of calling into
+//! a runtime directly. Implementations for `tokio`, `async-std` and
+//! `futures-timer` are available via the `rt-tokio`, `rt-async-std` and
+//! `rt-futures-timer` features.
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::future::{select, Either};
+
+/// A boxed future returned by `Timer::sleep`
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// This trait is used to wait for a given `Duration` without depending
+/// on a specific runtime.
+pub trait Timer: Send + Sync {
+    /// Return a future which completes after the given `duration`
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// The error returned by `timeout` if the deadline has elapsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+/// Run the given `future` and return `Err(Elapsed)` if it does not
+/// complete within `duration`.
+pub async fn timeout<F: Future>(
+    timer: &dyn Timer,
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    let future = Box::pin(future);
+    match select(future, timer.sleep(duration)).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Elapsed),
+    }
+}
+
+/// `Timer` implementation using `tokio::time`
+#[cfg(feature = "rt-tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTimer;
+
+#[cfg(feature = "rt-tokio")]
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::delay_for(duration))
+    }
+}
+
+/// `Timer` implementation using `async_std::task::sleep`
+#[cfg(feature = "rt-async-std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStdTimer;
+
+#[cfg(feature = "rt-async-std")]
+impl Timer for AsyncStdTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// `Timer` implementation using `futures_timer::Delay`. This timer
+/// does not need a runtime and also works on `wasm32-unknown-unknown`
+/// where it uses the timers of the browser.
+#[cfg(feature = "rt-futures-timer")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FuturesTimer;
+
+#[cfg(feature = "rt-futures-timer")]
+impl Timer for FuturesTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(futures_timer::Delay::new(duration))
+    }
+}
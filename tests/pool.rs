@@ -0,0 +1,242 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures::future::join_all;
+
+#[derive(Debug)]
+enum Error {
+    Fail,
+}
+
+struct Manager {
+    created: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+impl Manager {
+    fn new(fail: bool) -> (Manager, Arc<AtomicUsize>) {
+        let created = Arc::new(AtomicUsize::new(0));
+        let mgr = Manager {
+            created: created.clone(),
+            fail,
+        };
+        (mgr, created)
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<usize, Error> for Manager {
+    async fn create(&self) -> Result<usize, Error> {
+        if self.fail {
+            return Err(Error::Fail);
+        }
+        Ok(self.created.fetch_add(1, Ordering::Relaxed))
+    }
+    async fn recycle(&self, _obj: &mut usize) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+type Pool = deadpool::Pool<usize, Error>;
+
+#[tokio::main]
+#[test]
+async fn test_ensure_connected() {
+    let (mgr, created) = Manager::new(false);
+    let pool = Pool::new(mgr, 4);
+    pool.ensure_connected(2).await.unwrap();
+    assert_eq!(created.load(Ordering::Relaxed), 2);
+    // objects were returned to the pool and are reused
+    pool.ensure_connected(2).await.unwrap();
+    assert_eq!(created.load(Ordering::Relaxed), 2);
+    // n is capped at max_size
+    pool.ensure_connected(8).await.unwrap();
+    assert_eq!(created.load(Ordering::Relaxed), 4);
+}
+
+#[tokio::main]
+#[test]
+async fn test_ensure_connected_fail() {
+    let (mgr, _) = Manager::new(true);
+    let pool = Pool::new(mgr, 4);
+    assert!(pool.ensure_connected(1).await.is_err());
+}
+
+/// Future which returns `Pending` exactly once
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[derive(Default)]
+struct Creating {
+    current: AtomicUsize,
+    max: AtomicUsize,
+    total: AtomicUsize,
+}
+
+struct SlowManager {
+    creating: Arc<Creating>,
+}
+
+#[async_trait]
+impl deadpool::Manager<(), Error> for SlowManager {
+    async fn create(&self) -> Result<(), Error> {
+        self.creating.total.fetch_add(1, Ordering::Relaxed);
+        let current = self.creating.current.fetch_add(1, Ordering::Relaxed) + 1;
+        self.creating.max.fetch_max(current, Ordering::Relaxed);
+        for _ in 0..4 {
+            YieldNow(false).await;
+        }
+        self.creating.current.fetch_sub(1, Ordering::Relaxed);
+        Ok(())
+    }
+    async fn recycle(&self, _obj: &mut ()) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[tokio::main]
+#[test]
+async fn test_max_concurrent_creates() {
+    let creating = Arc::new(Creating::default());
+    let mgr = SlowManager {
+        creating: creating.clone(),
+    };
+    let mut config = deadpool::PoolConfig::new(8);
+    config.max_concurrent_creates = Some(2);
+    let pool = deadpool::Pool::from_config(mgr, config);
+    let objs = join_all((0..8).map(|_| pool.get())).await;
+    assert!(objs.iter().all(|obj| obj.is_ok()));
+    assert_eq!(creating.max.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::main]
+#[test]
+async fn test_max_concurrent_creates_reuse() {
+    let creating = Arc::new(Creating::default());
+    let mgr = SlowManager {
+        creating: creating.clone(),
+    };
+    let mut config = deadpool::PoolConfig::new(4);
+    config.max_concurrent_creates = Some(1);
+    let pool = deadpool::Pool::from_config(mgr, config);
+    // Every future returns its object right away, so the object created
+    // first is reused by the futures waiting for the permit.
+    join_all((0..4).map(|_| async {
+        pool.get().await.unwrap();
+    }))
+    .await;
+    assert_eq!(creating.total.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::main]
+#[test]
+async fn test_max_concurrent_creates_zero() {
+    let creating = Arc::new(Creating::default());
+    let mgr = SlowManager {
+        creating: creating.clone(),
+    };
+    let mut config = deadpool::PoolConfig::new(4);
+    // `Some(0)` must not block `get` forever
+    config.max_concurrent_creates = Some(0);
+    let pool = deadpool::Pool::from_config(mgr, config);
+    let objs = join_all((0..4).map(|_| pool.get())).await;
+    assert!(objs.iter().all(|obj| obj.is_ok()));
+    assert_eq!(creating.max.load(Ordering::Relaxed), 4);
+}
+
+#[tokio::main]
+#[test]
+async fn test_keyed_pool() {
+    let created = Arc::new(AtomicUsize::new(0));
+    let factory_created = created.clone();
+    let pool = deadpool::KeyedPool::new(move |_key: &&str| {
+        let mgr = Manager {
+            created: factory_created.clone(),
+            fail: false,
+        };
+        Pool::new(mgr, 2)
+    });
+    {
+        let _a = pool.get(&"a").await.unwrap();
+        let _b = pool.get(&"b").await.unwrap();
+        assert_eq!(pool.len(), 2);
+        // sub-pools in use are never evicted
+        assert_eq!(pool.evict_idle(), 0);
+    }
+    let _a = pool.get(&"a").await.unwrap();
+    assert_eq!(created.load(Ordering::Relaxed), 2);
+    // "b" is idle and was not used since the last eviction
+    assert_eq!(pool.evict_idle(), 1);
+    assert_eq!(pool.len(), 1);
+}
+
+#[tokio::main]
+#[test]
+async fn test_keyed_pool_max_size() {
+    let created = Arc::new(AtomicUsize::new(0));
+    let factory_created = created.clone();
+    let pool = deadpool::KeyedPool::with_max_size(
+        move |_key: &u32| {
+            let mgr = Manager {
+                created: factory_created.clone(),
+                fail: false,
+            };
+            Pool::new(mgr, 2)
+        },
+        2,
+    );
+    pool.get(&1).await.unwrap();
+    pool.get(&2).await.unwrap();
+    assert_eq!(pool.len(), 2);
+    // the least recently used idle sub-pool is evicted
+    let _obj = pool.get(&3).await.unwrap();
+    assert_eq!(pool.len(), 2);
+    assert_eq!(created.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::main]
+#[test]
+async fn test_chaos_manager() {
+    use deadpool::chaos::{ChaosConfig, ChaosManager};
+    let (mgr, _) = Manager::new(false);
+    let config = ChaosConfig {
+        create_fail_every: Some(2),
+        recycle_fail_every: Some(1),
+        ..Default::default()
+    };
+    let pool = Pool::new(ChaosManager::new(mgr, config, || Error::Fail), 4);
+    let obj = pool.get().await.unwrap();
+    assert!(pool.get().await.is_err());
+    drop(obj);
+    // recycling fails every time so a new object is created
+    assert!(pool.get().await.is_ok());
+}
+
+#[tokio::main]
+#[test]
+async fn test_object_take() {
+    let (mgr, created) = Manager::new(false);
+    let pool = Pool::new(mgr, 1);
+    let obj = pool.get().await.unwrap();
+    assert_eq!(deadpool::Object::take(obj), 0);
+    // the taken object made room for a new one
+    let obj = pool.get().await.unwrap();
+    assert_eq!(*obj, 1);
+    assert_eq!(created.load(Ordering::Relaxed), 2);
+}
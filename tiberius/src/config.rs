@@ -0,0 +1,142 @@
+use deadpool::PoolConfig;
+use serde::Deserialize;
+use tiberius::error::Error;
+use tiberius::{AuthMethod, Config as TiberiusConfig, EncryptionLevel};
+
+use crate::{Manager, Pool};
+
+/// This enum is used to configure the encryption of the connection. See
+/// `tiberius::EncryptionLevel`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Encryption {
+    /// Only encrypt the login procedure
+    Off,
+    /// Encrypt everything if the server supports it
+    On,
+    /// Do not encrypt anything
+    NotSupported,
+    /// Encrypt everything and fail if the server does not support it
+    Required,
+}
+
+impl From<Encryption> for EncryptionLevel {
+    fn from(encryption: Encryption) -> Self {
+        match encryption {
+            Encryption::Off => EncryptionLevel::Off,
+            Encryption::On => EncryptionLevel::On,
+            Encryption::NotSupported => EncryptionLevel::NotSupported,
+            Encryption::Required => EncryptionLevel::Required,
+        }
+    }
+}
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+///
+/// # Example (reading from the environment)
+///
+/// ```rust
+/// use deadpool_tiberius::Config;
+///
+/// std::env::set_var("MSSQL__HOST", "127.0.0.1");
+/// std::env::set_var("MSSQL__DBNAME", "deadpool");
+/// std::env::set_var("MSSQL__POOL__MAX_SIZE", "8");
+/// let cfg = Config::from_env("MSSQL").unwrap();
+/// assert_eq!(cfg.dbname, Some("deadpool".to_string()));
+/// assert_eq!(cfg.pool.unwrap().max_size, 8);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// ADO.NET connection string (e.g.
+    /// `server=tcp:host,1433;user=sa;password=secret`). The other
+    /// connection options override the values of the connection string.
+    pub connection_string: Option<String>,
+    /// See `tiberius::Config::host`
+    pub host: Option<String>,
+    /// See `tiberius::Config::port`
+    pub port: Option<u16>,
+    /// User for SQL Server authentication
+    pub user: Option<String>,
+    /// Password for SQL Server authentication
+    pub password: Option<String>,
+    /// See `tiberius::Config::database`
+    pub dbname: Option<String>,
+    /// See `tiberius::Config::application_name`
+    pub application_name: Option<String>,
+    /// See `tiberius::Config::encryption`
+    pub encryption: Option<Encryption>,
+    /// See `tiberius::Config::trust_cert`
+    pub trust_cert: Option<bool>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create new config object from an ADO.NET connection string
+    pub fn from_connection_string(connection_string: &str) -> Self {
+        Config {
+            connection_string: Some(connection_string.to_string()),
+            ..Self::default()
+        }
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `MSSQL__HOST`, `MSSQL__PORT` and `MSSQL__POOL__MAX_SIZE`
+    /// for the prefix `MSSQL`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create `tiberius::Config` from this configuration object. An error
+    /// is returned if the `connection_string` can not be parsed.
+    pub fn get_tiberius_config(&self) -> Result<TiberiusConfig, Error> {
+        let mut cfg = match &self.connection_string {
+            Some(connection_string) => TiberiusConfig::from_ado_string(connection_string)?,
+            None => TiberiusConfig::new(),
+        };
+        if let Some(host) = &self.host {
+            cfg.host(host);
+        }
+        if let Some(port) = self.port {
+            cfg.port(port);
+        }
+        if let Some(user) = &self.user {
+            cfg.authentication(AuthMethod::sql_server(
+                user,
+                self.password.as_deref().unwrap_or_default(),
+            ));
+        }
+        if let Some(dbname) = &self.dbname {
+            cfg.database(dbname);
+        }
+        if let Some(application_name) = &self.application_name {
+            cfg.application_name(application_name);
+        }
+        if let Some(encryption) = self.encryption {
+            cfg.encryption(encryption.into());
+        }
+        if self.trust_cert == Some(true) {
+            cfg.trust_cert();
+        }
+        Ok(cfg)
+    }
+    /// Create manager using this configuration object
+    pub fn create_manager(&self) -> Result<Manager, Error> {
+        Ok(Manager::new(self.get_tiberius_config()?))
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Result<Pool, Error> {
+        Ok(Pool::from_config(
+            self.create_manager()?,
+            self.pool.clone().unwrap_or_default(),
+        ))
+    }
+}
@@ -0,0 +1,103 @@
+//! Deadpool simple async pool for Microsoft SQL Server connections.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`tiberius`](https://crates.io/crates/tiberius) using
+//! `tokio` for the TCP connections.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_tiberius::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::from_ado_string(
+//!         "server=tcp:127.0.0.1,1433;user=sa;password=Deadpool-1;TrustServerCertificate=true",
+//!     )
+//!     .unwrap();
+//!     let pool = Pool::new(mgr, 16);
+//!     for i in 1..10i32 {
+//!         let mut client = pool.get().await.unwrap();
+//!         let row = client
+//!             .query("SELECT 1 + @P1", &[&i])
+//!             .await
+//!             .unwrap()
+//!             .into_row()
+//!             .await
+//!             .unwrap()
+//!             .unwrap();
+//!         assert_eq!(row.get::<i32, _>(0), Some(i + 1));
+//!     }
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use async_trait::async_trait;
+use tiberius::error::Error;
+use tiberius::{Config as TiberiusConfig, EncryptionLevel};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, Tokio02AsyncWriteCompatExt};
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::{Config, Encryption};
+
+/// The client type which is managed by the pool
+pub type Client = tiberius::Client<Compat<TcpStream>>;
+
+/// A type alias for using `deadpool::Pool` with `tiberius`
+pub type Pool = deadpool::Pool<Client, Error>;
+
+/// A type alias for using `deadpool::Object` with `tiberius`
+pub type Object = deadpool::Object<Client, Error>;
+
+/// The manager for creating and recyling SQL Server connections
+pub struct Manager {
+    config: TiberiusConfig,
+}
+
+impl Manager {
+    /// Create manager using the given `tiberius::Config`
+    pub fn new(config: TiberiusConfig) -> Self {
+        Manager { config }
+    }
+    /// Create manager using an ADO.NET connection string (e.g.
+    /// `server=tcp:host,1433;user=sa;password=secret;database=deadpool`)
+    pub fn from_ado_string(s: &str) -> Result<Self, Error> {
+        Ok(Self::new(TiberiusConfig::from_ado_string(s)?))
+    }
+    /// Create manager using a JDBC connection string (e.g.
+    /// `jdbc:sqlserver://host:1433;user=sa;password=secret`)
+    pub fn from_jdbc_string(s: &str) -> Result<Self, Error> {
+        Ok(Self::new(TiberiusConfig::from_jdbc_string(s)?))
+    }
+    /// Set the encryption level of new connections. See
+    /// `tiberius::Config::encryption`.
+    pub fn with_encryption(mut self, encryption: EncryptionLevel) -> Self {
+        self.config.encryption(encryption);
+        self
+    }
+    /// Accept the certificate of the server without validating it. This
+    /// should only be used for development and testing.
+    pub fn with_trust_cert(mut self) -> Self {
+        self.config.trust_cert();
+        self
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Client, Error> for Manager {
+    async fn create(&self) -> Result<Client, Error> {
+        let tcp = TcpStream::connect(self.config.get_addr()).await?;
+        tcp.set_nodelay(true)?;
+        tiberius::Client::connect(self.config.clone(), tcp.compat_write()).await
+    }
+    async fn recycle(&self, client: &mut Client) -> Result<(), Error> {
+        client.simple_query("SELECT 1").await?.into_row().await?;
+        Ok(())
+    }
+}
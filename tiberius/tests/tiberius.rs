@@ -0,0 +1,36 @@
+use std::env;
+
+use deadpool_tiberius::{Manager, Pool};
+
+fn create_pool() -> Pool {
+    let connection_string = env::var("MSSQL_CONNECTION_STRING").unwrap_or_else(|_| {
+        "server=tcp:127.0.0.1,1433;user=sa;password=Deadpool-1;TrustServerCertificate=true".into()
+    });
+    Pool::new(Manager::from_ado_string(&connection_string).unwrap(), 16)
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = create_pool();
+    let mut client = pool.get().await.unwrap();
+    let row = client
+        .query("SELECT 1 + @P1", &[&2i32])
+        .await
+        .unwrap()
+        .into_row()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.get::<i32, _>(0), Some(3));
+}
+
+#[tokio::main]
+#[test]
+async fn test_recycle() {
+    let pool = create_pool();
+    let client = pool.get().await.unwrap();
+    drop(client);
+    let mut client = pool.get().await.unwrap();
+    client.simple_query("SELECT 1").await.unwrap();
+}
@@ -0,0 +1,81 @@
+use deadpool::PoolConfig;
+use serde::Deserialize;
+use tonic::codegen::http::uri::InvalidUri;
+use tonic::transport::Endpoint;
+
+use crate::{Manager, Pool};
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+///
+/// # Example (reading from the environment)
+///
+/// ```rust
+/// use deadpool_tonic::Config;
+///
+/// std::env::set_var("GRPC__URL", "http://127.0.0.1:50051");
+/// std::env::set_var("GRPC__POOL__MAX_SIZE", "8");
+/// let cfg = Config::from_env("GRPC").unwrap();
+/// assert_eq!(cfg.url, "http://127.0.0.1:50051");
+/// assert_eq!(cfg.pool.unwrap().max_size, 8);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// URI of the endpoint (e.g. `http://127.0.0.1:50051`)
+    pub url: String,
+    /// See `tonic::transport::Endpoint::concurrency_limit`
+    pub concurrency_limit: Option<usize>,
+    /// See `tonic::transport::Endpoint::tcp_nodelay`
+    pub tcp_nodelay: Option<bool>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create new config object from an endpoint URI
+    pub fn from_url(url: &str) -> Self {
+        Config {
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `GRPC__URL` and `GRPC__POOL__MAX_SIZE` for the prefix
+    /// `GRPC`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create `tonic::transport::Endpoint` from this configuration object.
+    /// An error is returned if the `url` can not be parsed.
+    pub fn get_endpoint(&self) -> Result<Endpoint, InvalidUri> {
+        let mut endpoint = Endpoint::from_shared(self.url.clone())?;
+        if let Some(concurrency_limit) = self.concurrency_limit {
+            endpoint = endpoint.concurrency_limit(concurrency_limit);
+        }
+        if let Some(tcp_nodelay) = self.tcp_nodelay {
+            endpoint = endpoint.tcp_nodelay(tcp_nodelay);
+        }
+        Ok(endpoint)
+    }
+    /// Create manager using this configuration object
+    pub fn create_manager(&self) -> Result<Manager, InvalidUri> {
+        Ok(Manager::new(self.get_endpoint()?))
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Result<Pool, InvalidUri> {
+        Ok(Pool::from_config(
+            self.create_manager()?,
+            self.pool.clone().unwrap_or_default(),
+        ))
+    }
+}
@@ -0,0 +1,84 @@
+//! Deadpool simple async pool for gRPC channels.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`tonic`](https://crates.io/crates/tonic) channels.
+//!
+//! A `tonic::transport::Channel` multiplexes requests over a single HTTP/2
+//! connection. Pooling channels bounds the number of connections to an
+//! endpoint and makes their usage observable via `Pool::status`. Use
+//! `Pools` to manage one pool per endpoint which are created on demand.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use deadpool_tonic::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::from_uri("http://127.0.0.1:50051").unwrap();
+//!     let pool = Pool::new(mgr, 4);
+//!     let channel = pool.get().await.unwrap();
+//!     let mut client = GreeterClient::new(channel.clone());
+//!     client.say_hello(HelloRequest::default()).await.unwrap();
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use async_trait::async_trait;
+use futures::future::poll_fn;
+use tonic::body::BoxBody;
+use tonic::codegen::http::uri::InvalidUri;
+use tonic::codegen::{http::Request, Service};
+use tonic::transport::{Channel, Endpoint, Error};
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+mod pools;
+pub use pools::Pools;
+
+/// A type alias for using `deadpool::Pool` with `tonic`
+pub type Pool = deadpool::Pool<Channel, Error>;
+
+/// A type alias for using `deadpool::Object` with `tonic`
+pub type Object = deadpool::Object<Channel, Error>;
+
+/// The manager for creating and recyling gRPC channels
+pub struct Manager {
+    endpoint: Endpoint,
+}
+
+impl Manager {
+    /// Create manager using the given `tonic::transport::Endpoint`. Its
+    /// timeouts, TLS and keepalive settings are used for all channels.
+    pub fn new(endpoint: Endpoint) -> Self {
+        Manager { endpoint }
+    }
+    /// Create manager using the URI of the endpoint (e.g.
+    /// `http://127.0.0.1:50051`)
+    pub fn from_uri(uri: &str) -> Result<Self, InvalidUri> {
+        Ok(Self::new(Endpoint::from_shared(uri.to_owned())?))
+    }
+    /// Return the endpoint which is used to create channels
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Channel, Error> for Manager {
+    async fn create(&self) -> Result<Channel, Error> {
+        // Connect eagerly so connection errors are returned by `Pool::get`
+        // rather than by the first request.
+        self.endpoint.connect().await
+    }
+    async fn recycle(&self, channel: &mut Channel) -> Result<(), Error> {
+        // A channel whose connection failed does not become ready again.
+        poll_fn(|cx| <Channel as Service<Request<BoxBody>>>::poll_ready(channel, cx)).await
+    }
+}
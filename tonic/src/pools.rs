@@ -0,0 +1,90 @@
+use deadpool::KeyedPool;
+use tonic::codegen::http::Uri;
+use tonic::transport::{Endpoint, Error};
+
+use crate::{Manager, Object, Pool};
+
+/// A set of pools with one pool per endpoint which are created on demand.
+///
+/// Pools of endpoints which are not in use can be evicted in order to stay
+/// within a global connection budget. See `deadpool::KeyedPool`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use deadpool_tonic::Pools;
+///
+/// let pools = Pools::new(4);
+/// let channel = pools.get(&"http://backend1:50051".parse()?).await?;
+/// ```
+pub struct Pools {
+    pools: KeyedPool<Uri, tonic::transport::Channel, Error>,
+}
+
+impl Pools {
+    /// Create endpoint pools without a global limit. Every endpoint pool is
+    /// limited to `endpoint_max_size` channels.
+    pub fn new(endpoint_max_size: usize) -> Self {
+        Self::with_endpoint_config(endpoint_max_size, |endpoint| endpoint)
+    }
+    /// Create endpoint pools which use the given function to configure the
+    /// endpoints, e.g. to set timeouts or TLS settings. Every endpoint pool
+    /// is limited to `endpoint_max_size` channels.
+    pub fn with_endpoint_config<F>(endpoint_max_size: usize, configure: F) -> Self
+    where
+        F: Fn(Endpoint) -> Endpoint + Send + Sync + 'static,
+    {
+        Pools {
+            pools: KeyedPool::new(factory(configure, endpoint_max_size)),
+        }
+    }
+    /// Create endpoint pools which are limited to `max_size` channels in
+    /// total. Every endpoint pool is limited to `endpoint_max_size`
+    /// channels.
+    ///
+    /// See `deadpool::KeyedPool::with_max_size` for how the global limit is
+    /// enforced.
+    pub fn with_max_size(endpoint_max_size: usize, max_size: usize) -> Self {
+        Pools {
+            pools: KeyedPool::with_max_size(
+                factory(|endpoint| endpoint, endpoint_max_size),
+                max_size,
+            ),
+        }
+    }
+    /// Retrieve a channel to the given endpoint
+    pub async fn get(&self, uri: &Uri) -> Result<Object, Error> {
+        self.pools.get(uri).await
+    }
+    /// Remove the pools of all endpoints which are not in use and have not
+    /// been used since the last call to this method. Returns the number of
+    /// removed pools.
+    pub fn evict_idle(&self) -> usize {
+        self.pools.evict_idle()
+    }
+    /// Remove the pool of the given endpoint
+    pub fn remove(&self, uri: &Uri) -> Option<Pool> {
+        self.pools.remove(uri)
+    }
+    /// Number of endpoints which currently have a pool
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+    /// Returns `true` if no endpoint has a pool
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+}
+
+fn factory<F>(
+    configure: F,
+    endpoint_max_size: usize,
+) -> impl Fn(&Uri) -> Pool + Send + Sync + 'static
+where
+    F: Fn(Endpoint) -> Endpoint + Send + Sync + 'static,
+{
+    move |uri| {
+        let endpoint = configure(Endpoint::from(uri.clone()));
+        Pool::new(Manager::new(endpoint), endpoint_max_size)
+    }
+}
@@ -0,0 +1,34 @@
+use std::env;
+
+use deadpool_tonic::{Manager, Pool, Pools};
+
+fn grpc_url() -> String {
+    env::var("GRPC_URL").unwrap_or_else(|_| "http://127.0.0.1:50051".into())
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = Pool::new(Manager::from_uri(&grpc_url()).unwrap(), 2);
+    let channel = pool.get().await.unwrap();
+    drop(channel);
+    pool.get().await.unwrap();
+}
+
+#[tokio::main]
+#[test]
+async fn test_pools() {
+    let pools = Pools::new(2);
+    let uri = grpc_url().parse().unwrap();
+    pools.get(&uri).await.unwrap();
+    pools.get(&uri).await.unwrap();
+    assert_eq!(pools.len(), 1);
+    assert!(pools.remove(&uri).is_some());
+    assert!(pools.is_empty());
+}
+
+#[tokio::main]
+#[test]
+async fn test_invalid_uri() {
+    assert!(Manager::from_uri("not a uri").is_err());
+}
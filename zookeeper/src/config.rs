@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use deadpool::PoolConfig;
+use serde::Deserialize;
+
+use crate::{Manager, Pool};
+
+/// Configuration object which can be read from configuration files and
+/// environment variables and is used to create a `Pool`.
+///
+/// # Example (reading from the environment)
+///
+/// ```rust
+/// use deadpool_zookeeper::Config;
+///
+/// std::env::set_var("ZK__CONNECT_STRING", "zk1:2181,zk2:2181");
+/// std::env::set_var("ZK__POOL__MAX_SIZE", "8");
+/// let cfg = Config::from_env("ZK").unwrap();
+/// assert_eq!(cfg.connect_string, Some("zk1:2181,zk2:2181".to_string()));
+/// assert_eq!(cfg.pool.unwrap().max_size, 8);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// Connect string (e.g. `zk1:2181,zk2:2181/chroot`). Defaults to
+    /// `127.0.0.1:2181`.
+    pub connect_string: Option<String>,
+    /// Session timeout in milliseconds. See
+    /// `Manager::with_session_timeout`.
+    #[serde(default, deserialize_with = "deadpool::serde_duration::millis")]
+    pub session_timeout: Option<Duration>,
+    /// Pool configuration
+    pub pool: Option<PoolConfig>,
+}
+
+impl Config {
+    /// Create new config object
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create configuration from environment variables. The variables are
+    /// expected to start with `prefix` and use `__` as separator for nested
+    /// values, e.g. `ZK__CONNECT_STRING` and `ZK__POOL__MAX_SIZE` for the
+    /// prefix `ZK`.
+    pub fn from_env(prefix: &str) -> Result<Self, config_crate::ConfigError> {
+        let mut cfg = config_crate::Config::new();
+        cfg.merge(config_crate::Environment::new().separator("__"))?;
+        match cfg.get(&prefix.to_lowercase()) {
+            Err(config_crate::ConfigError::NotFound(_)) => Ok(Self::default()),
+            result => result,
+        }
+    }
+    /// Create manager using this configuration object. Session hooks can
+    /// be added to the returned manager.
+    pub fn create_manager(&self) -> Manager {
+        let mut manager = Manager::new(self.connect_string.as_deref().unwrap_or("127.0.0.1:2181"));
+        if let Some(session_timeout) = self.session_timeout {
+            manager = manager.with_session_timeout(session_timeout);
+        }
+        manager
+    }
+    /// Create pool using this configuration object
+    pub fn create_pool(&self) -> Pool {
+        Pool::from_config(self.create_manager(), self.pool.clone().unwrap_or_default())
+    }
+}
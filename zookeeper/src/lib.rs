@@ -0,0 +1,229 @@
+//! Deadpool simple async pool for ZooKeeper sessions.
+//!
+//! This crate implements a [`deadpool`](https://crates.io/crates/deadpool)
+//! manager for [`zookeeper`](https://crates.io/crates/zookeeper). The
+//! client of `zookeeper` is blocking, so sessions are created and used on
+//! the blocking thread pool of `tokio` via `Session::interact`.
+//!
+//! The state of every session is tracked. Sessions which were closed (e.g.
+//! because they expired) are discarded when they are recycled. Sessions
+//! which are reconnecting are kept as the client reconnects on its own.
+//!
+//! Watches are not restored after a reconnect. Use
+//! `Manager::with_session_hook` to register them again.
+//!
+//! You should not need to use `deadpool` directly. Use the `Pool` type
+//! provided by this crate instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deadpool_zookeeper::{Manager, Pool};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mgr = Manager::new("127.0.0.1:2181").with_session_hook(|zk| {
+//!         zk.exists_w("/deadpool", |event| println!("{:?}", event))?;
+//!         Ok(())
+//!     });
+//!     let pool = Pool::new(mgr, 4);
+//!     let session = pool.get().await.unwrap();
+//!     let children = session
+//!         .interact(|zk| zk.get_children("/", false))
+//!         .await
+//!         .unwrap()
+//!         .unwrap();
+//!     println!("{:?}", children);
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::task::spawn_blocking;
+use zookeeper::{WatchedEvent, ZkError, ZkResult, ZkState, ZooKeeper};
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::Config;
+
+/// A type alias for using `deadpool::Pool` with `zookeeper`
+pub type Pool = deadpool::Pool<Session, Error>;
+
+/// A type alias for using `deadpool::Object` with `zookeeper`
+pub type Object = deadpool::Object<Session, Error>;
+
+/// Hook which is called with every new session and every session which
+/// has reconnected. See `Manager::with_session_hook`.
+pub type SessionHook = Arc<dyn Fn(&ZooKeeper) -> ZkResult<()> + Send + Sync>;
+
+/// This error is returned by the `Manager` and `Session::interact`
+#[derive(Debug)]
+pub enum Error {
+    /// The `zookeeper` client returned an error
+    Backend(ZkError),
+    /// The session was closed or authentication failed
+    Closed(ZkState),
+    /// The closure passed to `Session::interact` panicked
+    Panic,
+}
+
+impl From<ZkError> for Error {
+    fn from(e: ZkError) -> Self {
+        Error::Backend(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Backend(e) => write!(f, "{}", e),
+            Error::Closed(state) => write!(f, "Session is no longer usable: {:?}", state),
+            Error::Panic => write!(f, "Interaction with the session panicked"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The manager for creating and recyling ZooKeeper sessions
+pub struct Manager {
+    connect_string: String,
+    session_timeout: Duration,
+    session_hooks: Vec<SessionHook>,
+}
+
+impl Manager {
+    /// Create manager using the given connect string (e.g.
+    /// `zk1:2181,zk2:2181,zk3:2181/chroot`)
+    pub fn new(connect_string: &str) -> Self {
+        Manager {
+            connect_string: connect_string.to_owned(),
+            session_timeout: Duration::from_secs(10),
+            session_hooks: Vec::new(),
+        }
+    }
+    /// Set the session timeout. Defaults to 10 seconds.
+    pub fn with_session_timeout(mut self, session_timeout: Duration) -> Self {
+        self.session_timeout = session_timeout;
+        self
+    }
+    /// Add a hook which is called on the blocking thread pool with every
+    /// new session and with every session which has reconnected to the
+    /// ensemble before it is handed out. Use it to register watches. If the
+    /// hook fails the session is discarded.
+    pub fn with_session_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ZooKeeper) -> ZkResult<()> + Send + Sync + 'static,
+    {
+        self.session_hooks.push(Arc::new(hook));
+        self
+    }
+    async fn run_session_hooks(&self, session: &Session) -> Result<(), Error> {
+        let hooks = self.session_hooks.clone();
+        session
+            .interact(move |zk| hooks.iter().try_for_each(|hook| hook(zk)))
+            .await??;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl deadpool::Manager<Session, Error> for Manager {
+    async fn create(&self) -> Result<Session, Error> {
+        let connect_string = self.connect_string.clone();
+        let session_timeout = self.session_timeout;
+        let zk = spawn_blocking(move || {
+            ZooKeeper::connect(&connect_string, session_timeout, |_: WatchedEvent| {})
+        })
+        .await
+        .map_err(|_| Error::Panic)??;
+        let session = Session::new(zk);
+        // The listener is added after connecting, so it might have missed
+        // the `Connected` event, which would also hide the next reconnect.
+        session.mark_connected();
+        if !self.session_hooks.is_empty() {
+            self.run_session_hooks(&session).await?;
+        }
+        Ok(session)
+    }
+    async fn recycle(&self, session: &mut Session) -> Result<(), Error> {
+        match session.state() {
+            state @ ZkState::Closed | state @ ZkState::AuthFailed => Err(Error::Closed(state)),
+            _ => {
+                if session.reconnected.swap(false, Ordering::Relaxed) {
+                    self.run_session_hooks(session).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The pooled object which provides access to the `ZooKeeper` client via
+/// `Session::interact`.
+pub struct Session {
+    zk: Arc<ZooKeeper>,
+    state: Arc<Mutex<ZkState>>,
+    /// Set when the session has reconnected after it lost the connection
+    reconnected: Arc<AtomicBool>,
+}
+
+impl Session {
+    fn new(zk: ZooKeeper) -> Self {
+        let state = Arc::new(Mutex::new(ZkState::Connecting));
+        let reconnected = Arc::new(AtomicBool::new(false));
+        let listener_state = state.clone();
+        let listener_reconnected = reconnected.clone();
+        let lost = AtomicBool::new(false);
+        zk.add_listener(move |new_state| {
+            let mut state = listener_state.lock().unwrap();
+            match new_state {
+                ZkState::Connecting | ZkState::NotConnected if is_connected(*state) => {
+                    lost.store(true, Ordering::Relaxed);
+                }
+                ZkState::Connected if lost.swap(false, Ordering::Relaxed) => {
+                    listener_reconnected.store(true, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+            *state = new_state;
+        });
+        Session {
+            zk: Arc::new(zk),
+            state,
+            reconnected,
+        }
+    }
+    fn mark_connected(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !is_connected(*state) {
+            *state = ZkState::Connected;
+        }
+    }
+    /// Return the last known state of the session
+    pub fn state(&self) -> ZkState {
+        *self.state.lock().unwrap()
+    }
+    /// Run the given closure on the blocking thread pool passing it the
+    /// `ZooKeeper` client and return its result.
+    pub async fn interact<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&ZooKeeper) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let zk = self.zk.clone();
+        spawn_blocking(move || f(&zk))
+            .await
+            .map_err(|_| Error::Panic)
+    }
+}
+
+fn is_connected(state: ZkState) -> bool {
+    matches!(state, ZkState::Connected | ZkState::ConnectedReadOnly)
+}
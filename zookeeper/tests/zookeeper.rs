@@ -0,0 +1,42 @@
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use deadpool_zookeeper::{Manager, Pool};
+use zookeeper::ZkState;
+
+fn connect_string() -> String {
+    env::var("ZK_CONNECT_STRING").unwrap_or_else(|_| "127.0.0.1:2181".into())
+}
+
+#[tokio::main]
+#[test]
+async fn test_basic() {
+    let pool = Pool::new(Manager::new(&connect_string()), 2);
+    let session = pool.get().await.unwrap();
+    assert_eq!(session.state(), ZkState::Connected);
+    let children = session
+        .interact(|zk| zk.get_children("/", false))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(children.contains(&"zookeeper".to_string()));
+}
+
+#[tokio::main]
+#[test]
+async fn test_session_hook() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let hook_calls = calls.clone();
+    let mgr = Manager::new(&connect_string()).with_session_hook(move |zk| {
+        zk.exists("/", false)?;
+        hook_calls.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    });
+    let pool = Pool::new(mgr, 1);
+    let session = pool.get().await.unwrap();
+    assert_eq!(session.state(), ZkState::Connected);
+    drop(session);
+    pool.get().await.unwrap();
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}